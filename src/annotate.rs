@@ -0,0 +1,131 @@
+use crate::parse::{parse_with, ParseOptions};
+
+/// Numbers shorter than this are more likely counts or IDs than times. Shorter
+/// epochs are valid times too, so this is a heuristic: 9 digits covers every
+/// epoch in seconds from 1973 onward.
+const MIN_DIGITS: usize = 9;
+
+/// Finds numbers in `line` that parse as times, and either replaces them with,
+/// or follows them by, the time in RFC3339 in `options.zone`, e.g.
+/// `took 1700000000` becomes `took 1700000000 (2023-11-14T22:13:20+00:00)`.
+///
+/// Only standalone runs of at least 9 digits, with an optional fraction, are
+/// considered, so digits inside words, IP addresses and version numbers are
+/// left alone. They are read the same way as `parse_with` would read them on
+/// their own, so anything outside 1900 to 2500 is left alone too.
+pub fn annotate_line(line: &str, options: &ParseOptions, replace: bool) -> String {
+    let bytes = line.as_bytes();
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'.';
+    let mut out = String::with_capacity(line.len());
+    let mut copied = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() || (i > 0 && is_word(bytes[i - 1])) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        let whole = i - start;
+        if i + 1 < bytes.len() && bytes[i] == b'.' && bytes[i + 1].is_ascii_digit() {
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+        }
+        // A trailing `.` is fine, since it may end a sentence, unless more of
+        // a version number or address follows.
+        let followed_by_word = match bytes.get(i) {
+            Some(b'.') => bytes.get(i + 1).map_or(false, |&b| is_word(b)),
+            Some(&b) => is_word(b),
+            None => false,
+        };
+        if whole < MIN_DIGITS || followed_by_word {
+            continue;
+        }
+
+        let token = &line[start..i];
+        if let Ok(parsed) = parse_with(token, options) {
+            let offset = options.zone.offset_at(parsed.time);
+            let rfc3339 = parsed.time.with_timezone(&offset).to_rfc3339();
+            out.push_str(&line[copied..start]);
+            if replace {
+                out.push_str(&rfc3339);
+            } else {
+                out.push_str(token);
+                out.push_str(" (");
+                out.push_str(&rfc3339);
+                out.push(')');
+            }
+            copied = i;
+        }
+    }
+    out.push_str(&line[copied..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::{DateTime, Utc};
+
+    fn annotate(line: &str, zone: &str, replace: bool) -> String {
+        let now = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let options = ParseOptions {
+            zone: zone.parse().unwrap(),
+            ..ParseOptions::new(now)
+        };
+        annotate_line(line, &options, replace)
+    }
+
+    #[test]
+    fn follows_epochs_by_the_time() {
+        assert_eq!(
+            annotate("took 1700000000 ms", "UTC", false),
+            "took 1700000000 (2023-11-14T22:13:20+00:00) ms"
+        );
+        assert_eq!(
+            annotate("at 1700000000.5.", "UTC", false),
+            "at 1700000000.5 (2023-11-14T22:13:20.500+00:00)."
+        );
+        assert_eq!(
+            annotate("ts=1700000000123,", "UTC", false),
+            "ts=1700000000123 (2023-11-14T22:13:20.123+00:00),"
+        );
+    }
+
+    #[test]
+    fn replaces_epochs() {
+        assert_eq!(
+            annotate("[1700000000] started", "UTC", true),
+            "[2023-11-14T22:13:20+00:00] started"
+        );
+    }
+
+    #[test]
+    fn uses_the_zone() {
+        assert_eq!(
+            annotate("1700000000", "Asia/Tokyo", false),
+            "1700000000 (2023-11-15T07:13:20+09:00)"
+        );
+    }
+
+    #[test]
+    fn leaves_other_numbers_alone() {
+        for line in &[
+            "id_1700000000 and x1700000000y",
+            "version 1.2.1700000000",
+            "version 1700000000.1.2",
+            "host 10.0.0.1 port 8080",
+            "count 12345678",
+            "far 99999999999999999999",
+        ] {
+            assert_eq!(annotate(line, "UTC", false), *line);
+        }
+    }
+}
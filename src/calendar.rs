@@ -0,0 +1,77 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Timelike, Utc};
+
+use crate::span::shift_months;
+
+/// A unit of the clock or calendar that wall-clock times can be rounded to or
+/// moved by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Period {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl Period {
+    /// The start of the period containing `wall`. Weeks start on Monday.
+    pub fn floor(self, wall: NaiveDateTime) -> NaiveDateTime {
+        let date = wall.date();
+        match self {
+            Period::Second => date.and_hms(wall.hour(), wall.minute(), wall.second()),
+            Period::Minute => date.and_hms(wall.hour(), wall.minute(), 0),
+            Period::Hour => date.and_hms(wall.hour(), 0, 0),
+            Period::Day => date.and_hms(0, 0, 0),
+            Period::Week => (date - Duration::days(date.weekday().num_days_from_monday() as i64))
+                .and_hms(0, 0, 0),
+            Period::Month => NaiveDate::from_ymd(date.year(), date.month(), 1).and_hms(0, 0, 0),
+            Period::Quarter => {
+                NaiveDate::from_ymd(date.year(), date.month0() / 3 * 3 + 1, 1).and_hms(0, 0, 0)
+            }
+            Period::Year => NaiveDate::from_ymd(date.year(), 1, 1).and_hms(0, 0, 0),
+        }
+    }
+
+    /// Moves `wall` by `n` whole periods. Months, quarters and years clamp the
+    /// day to the end of a shorter month.
+    pub fn shift(self, wall: NaiveDateTime, n: i64) -> Option<NaiveDateTime> {
+        let months = |months: i64| {
+            shift_months(wall.date(), n.checked_mul(months)?).map(|d| d.and_time(wall.time()))
+        };
+        let exact = |secs: i64| {
+            let secs = n.checked_mul(secs)?;
+            // `Duration` holds milliseconds, and panics rather than overflow.
+            secs.checked_mul(1000)?;
+            wall.checked_add_signed(Duration::seconds(secs))
+        };
+        match self {
+            Period::Second => exact(1),
+            Period::Minute => exact(60),
+            Period::Hour => exact(3600),
+            Period::Day => exact(86400),
+            Period::Week => exact(7 * 86400),
+            Period::Month => months(1),
+            Period::Quarter => months(3),
+            Period::Year => months(12),
+        }
+    }
+
+    /// Whether the period has a fixed length whatever the clocks in a zone do,
+    /// which is true of hours and smaller.
+    pub fn is_exact(self) -> bool {
+        match self {
+            Period::Second | Period::Minute | Period::Hour => true,
+            Period::Day | Period::Week | Period::Month | Period::Quarter | Period::Year => false,
+        }
+    }
+
+    /// Moves the instant `t` by `n` whole periods of fixed length, rather than
+    /// moving its wall-clock time in some zone.
+    pub fn shift_exact(self, t: DateTime<Utc>, n: i64) -> Option<DateTime<Utc>> {
+        self.shift(t.naive_utc(), n)
+            .map(|naive| DateTime::<Utc>::from_utc(naive, Utc))
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+
+use crate::calendar::Period;
+use crate::zone::{DstIssue, Zone};
+
+/// Parses Elasticsearch-style date math: an anchor followed by any number of
+/// operations, e.g. `now-7d/d`, `now/w` or `2024-01-01||+1M/d`.
+///
+/// The anchor is either `now` or a date followed by `||`, which is handed to
+/// `parse_anchor`. Each operation is `+N<unit>` or `-N<unit>` to add or
+/// subtract, or `/<unit>` to round down to the start of that unit. The units
+/// are `y`ears, `M`onths, `w`eeks, `d`ays, `h`ours (or `H`), `m`inutes and
+/// `s`econds. Years, months, weeks and days, as well as all rounding, follow
+/// the calendar in `zone`.
+pub fn parse_date_math<F>(
+    s: &str,
+    now: DateTime<Utc>,
+    zone: Zone,
+    parse_anchor: F,
+) -> Result<(DateTime<Utc>, Option<DstIssue>), String>
+where
+    F: Fn(&str) -> Result<DateTime<Utc>, String>,
+{
+    let s = s.trim();
+    let (mut t, ops) = if let Some(ops) = s.strip_prefix("now") {
+        if ops.is_empty() {
+            return Err("no date math operations after now".to_string());
+        }
+        (now, ops)
+    } else if let Some((anchor, ops)) = s.split_once("||") {
+        (parse_anchor(anchor)?, ops)
+    } else {
+        return Err("not date math: expected now or an anchor date followed by ||".to_string());
+    };
+
+    let mut dst = None;
+    let mut rest = ops;
+    while let Some(op) = rest.chars().next() {
+        rest = &rest[op.len_utf8()..];
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let n = match (op, digits) {
+            ('/', 0) => 0,
+            ('+', d) | ('-', d) if d > 0 => rest[..d]
+                .parse::<i64>()
+                .map_err(|e| format!("{} in {:?}", e, &rest[..d]))?,
+            _ => {
+                return Err(format!(
+                    "expected +N, -N or / followed by a unit at {:?}",
+                    op
+                ))
+            }
+        };
+        rest = &rest[digits..];
+        let unit = rest
+            .chars()
+            .next()
+            .ok_or_else(|| "missing unit at end of expression".to_string())?;
+        rest = &rest[unit.len_utf8()..];
+        let n = if op == '-' { -n } else { n };
+
+        let period = match unit {
+            'y' => Period::Year,
+            'M' => Period::Month,
+            'w' => Period::Week,
+            'd' => Period::Day,
+            'h' | 'H' => Period::Hour,
+            'm' => Period::Minute,
+            's' => Period::Second,
+            _ => return Err(format!("unknown unit {:?}", unit)),
+        };
+        let wall = zone.wall_clock(t);
+        let moved = match (op, period) {
+            ('/', _) => Some(period.floor(wall)),
+            _ if period.is_exact() => {
+                t = period
+                    .shift_exact(t, n)
+                    .ok_or_else(|| "date is out of range".to_string())?;
+                continue;
+            }
+            _ => period.shift(wall, n),
+        };
+        let (resolved, issue) =
+            zone.resolve(moved.ok_or_else(|| "date is out of range".to_string())?);
+        t = resolved;
+        dst = issue;
+    }
+    Ok((t, dst))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn eval(s: &str, now: &str, zone: &str) -> Result<DateTime<Utc>, String> {
+        let anchor = |a: &str| {
+            DateTime::parse_from_rfc3339(&format!("{}T00:00:00Z", a))
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|e| e.to_string())
+        };
+        parse_date_math(s, at(now), zone.parse().unwrap(), anchor).map(|(t, _)| t)
+    }
+
+    #[test]
+    fn rounds_and_shifts() {
+        let now = "2024-03-15T12:34:56Z";
+        assert_eq!(eval("now-7d/d", now, "UTC"), Ok(at("2024-03-08T00:00:00Z")));
+        assert_eq!(eval("now/w", now, "UTC"), Ok(at("2024-03-11T00:00:00Z")));
+        assert_eq!(eval("now+90m", now, "UTC"), Ok(at("2024-03-15T14:04:56Z")));
+        assert_eq!(eval("now/M-1M", now, "UTC"), Ok(at("2024-02-01T00:00:00Z")));
+        assert_eq!(
+            eval("2024-01-31||+1M/d", now, "UTC"),
+            Ok(at("2024-02-29T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn days_follow_the_calendar_and_hours_are_exact() {
+        // Clocks in New York skipped an hour on 2024-03-10.
+        let now = "2024-03-10T18:00:00Z";
+        let zone = "America/New_York";
+        assert_eq!(eval("now-1d", now, zone), Ok(at("2024-03-09T19:00:00Z")));
+        assert_eq!(eval("now-24h", now, zone), Ok(at("2024-03-09T18:00:00Z")));
+        assert_eq!(eval("now/d", now, zone), Ok(at("2024-03-10T05:00:00Z")));
+    }
+
+    #[test]
+    fn rejects_non_ascii_operators_and_units() {
+        let now = "2024-03-15T12:34:56Z";
+        for s in &["nowé", "now-1d°", "2024-01-01||é", "now+1é"] {
+            assert!(eval(s, now, "UTC").is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn rejects_out_of_range_shifts() {
+        let now = "2024-03-15T12:34:56Z";
+        assert!(eval("now+9223372036854775807s", now, "UTC").is_err());
+        assert!(eval("now+9223372036854775807y", now, "UTC").is_err());
+        assert!(eval("now+9223372036854775807M", now, "UTC").is_err());
+        assert!(eval("now-9223372036854775807M", now, "UTC").is_err());
+    }
+}
@@ -0,0 +1,297 @@
+use std::fmt;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, Utc};
+use serde::Serialize;
+
+use crate::span::shift_months;
+use crate::zone::Zone;
+
+/// A signed length of time split into calendar units, largest first.
+///
+/// Whole months are counted on the calendar, so January 31st to February 29th
+/// is one month, and the rest is split into days, hours, minutes and seconds.
+/// All fields share the sign of the whole.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Breakdown {
+    pub years: i64,
+    pub months: i64,
+    pub days: i64,
+    pub hours: i64,
+    pub minutes: i64,
+    pub seconds: i64,
+    pub nanoseconds: i64,
+}
+
+impl Breakdown {
+    /// The calendar distance from the wall-clock time `from` to `to`, negative
+    /// if `to` is earlier.
+    pub fn between(from: NaiveDateTime, to: NaiveDateTime) -> Breakdown {
+        let (sign, start, end) = if to < from {
+            (-1, to, from)
+        } else {
+            (1, from, to)
+        };
+        let after_months =
+            |months| shift_months(start.date(), months).map(|date| date.and_time(start.time()));
+        let mut months =
+            (end.year() - start.year()) as i64 * 12 + end.month() as i64 - start.month() as i64;
+        while months > 0 && after_months(months).map_or(true, |t| t > end) {
+            months -= 1;
+        }
+        let rest = Breakdown::from_duration(end - after_months(months).unwrap_or(start));
+        Breakdown {
+            years: sign * (months / 12),
+            months: sign * (months % 12),
+            days: sign * rest.days,
+            hours: sign * rest.hours,
+            minutes: sign * rest.minutes,
+            seconds: sign * rest.seconds,
+            nanoseconds: sign * rest.nanoseconds,
+        }
+    }
+
+    /// Splits a fixed length of time into days, hours, minutes and seconds.
+    pub fn from_duration(d: Duration) -> Breakdown {
+        let sign = if d < Duration::zero() { -1 } else { 1 };
+        let d = if sign < 0 { -d } else { d };
+        let subsec = d - Duration::seconds(d.num_seconds());
+        Breakdown {
+            years: 0,
+            months: 0,
+            days: sign * d.num_days(),
+            hours: sign * (d.num_hours() % 24),
+            minutes: sign * (d.num_minutes() % 60),
+            seconds: sign * (d.num_seconds() % 60),
+            nanoseconds: sign * subsec.num_nanoseconds().unwrap_or(0),
+        }
+    }
+
+    fn is_negative(&self) -> bool {
+        [
+            self.years,
+            self.months,
+            self.days,
+            self.hours,
+            self.minutes,
+            self.seconds,
+            self.nanoseconds,
+        ]
+        .iter()
+        .any(|&n| n < 0)
+    }
+
+    /// Describes the distance the way people say it, e.g. `3 years, 2 months,
+    /// 5 days ago` or `in 4 hours, 12 minutes`, with at most `units` units
+    /// starting from the largest one that isn't zero. Smaller units are
+    /// truncated. `units` of 0 keeps them all.
+    pub fn humanize(&self, units: usize) -> String {
+        let all = [
+            (self.years, "year"),
+            (self.months, "month"),
+            (self.days, "day"),
+            (self.hours, "hour"),
+            (self.minutes, "minute"),
+            (self.seconds, "second"),
+        ];
+        let first = match all.iter().position(|&(n, _)| n != 0) {
+            Some(first) => first,
+            None => return "now".to_string(),
+        };
+        let last = if units == 0 {
+            all.len()
+        } else {
+            all.len().min(first + units)
+        };
+        let parts: Vec<String> = all[first..last]
+            .iter()
+            .filter(|&&(n, _)| n != 0)
+            .map(|&(n, unit)| {
+                let n = n.abs();
+                format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" })
+            })
+            .collect();
+        if self.is_negative() {
+            format!("{} ago", parts.join(", "))
+        } else {
+            format!("in {}", parts.join(", "))
+        }
+    }
+}
+
+impl fmt::Display for Breakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts: Vec<String> = [
+            (self.years, "year"),
+            (self.months, "month"),
+            (self.days, "day"),
+            (self.hours, "hour"),
+            (self.minutes, "minute"),
+        ]
+        .iter()
+        .filter(|&&(n, _)| n != 0)
+        .map(|&(n, unit)| {
+            let n = n.abs();
+            format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" })
+        })
+        .collect();
+        if self.nanoseconds != 0 {
+            let frac = format!("{:09}", self.nanoseconds.abs());
+            parts.push(format!(
+                "{}.{} seconds",
+                self.seconds.abs(),
+                frac.trim_end_matches('0')
+            ));
+        } else if self.seconds != 0 {
+            let n = self.seconds.abs();
+            parts.push(format!("{} second{}", n, if n == 1 { "" } else { "s" }));
+        }
+        if parts.is_empty() {
+            return write!(f, "0 seconds");
+        }
+        if self.is_negative() {
+            write!(f, "minus ")?;
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// The signed distance from one instant to another, e.g. the length of an
+/// incident between two log timestamps. Negative when `to` is earlier.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diff {
+    pub from: String,
+    pub to: String,
+    pub seconds: i64,
+    pub milliseconds: i64,
+    pub hours: i64,
+    pub days: i64,
+    /// The distance in calendar units, following the calendar in the zone the
+    /// diff was computed in.
+    pub calendar: Breakdown,
+}
+
+impl Diff {
+    pub fn new(from: DateTime<Utc>, to: DateTime<Utc>, zone: Zone) -> Diff {
+        let d = to - from;
+        Diff {
+            from: from.to_rfc3339(),
+            to: to.to_rfc3339(),
+            seconds: d.num_seconds(),
+            milliseconds: d.num_milliseconds(),
+            hours: d.num_hours(),
+            days: d.num_days(),
+            calendar: Breakdown::between(zone.wall_clock(from), zone.wall_clock(to)),
+        }
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:20}{}", "From:", self.from)?;
+        writeln!(f, "{:20}{}", "To:", self.to)?;
+        writeln!(f)?;
+        writeln!(f, "{:20}{}", "Seconds:", self.seconds)?;
+        writeln!(f, "{:20}{}", "Milliseconds:", self.milliseconds)?;
+        writeln!(f, "{:20}{}", "Hours:", self.hours)?;
+        writeln!(f, "{:20}{}", "Days:", self.days)?;
+        writeln!(f, "{:20}{}", "Calendar:", self.calendar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wall(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn breakdown(years: i64, months: i64, days: i64, hours: i64) -> Breakdown {
+        Breakdown {
+            years,
+            months,
+            days,
+            hours,
+            minutes: 0,
+            seconds: 0,
+            nanoseconds: 0,
+        }
+    }
+
+    #[test]
+    fn clamps_months_to_the_end_of_a_shorter_month() {
+        let jan31 = wall("2024-01-31 00:00:00");
+        let mar1 = wall("2024-03-01 00:00:00");
+        assert_eq!(Breakdown::between(jan31, mar1), breakdown(0, 1, 1, 0));
+        assert_eq!(
+            Breakdown::between(jan31, mar1).to_string(),
+            "1 month, 1 day"
+        );
+        assert_eq!(
+            Breakdown::between(wall("2021-02-28 12:00:00"), wall("2024-02-29 18:00:00")),
+            breakdown(3, 0, 1, 6)
+        );
+    }
+
+    #[test]
+    fn earlier_targets_are_negative() {
+        let jan31 = wall("2024-01-31 00:00:00");
+        let mar1 = wall("2024-03-01 00:00:00");
+        assert_eq!(Breakdown::between(mar1, jan31), breakdown(0, -1, -1, 0));
+        assert_eq!(
+            Breakdown::between(mar1, jan31).to_string(),
+            "minus 1 month, 1 day"
+        );
+        assert_eq!(
+            Breakdown::from_duration(Duration::milliseconds(-90_500)).to_string(),
+            "minus 1 minute, 30.5 seconds"
+        );
+    }
+
+    #[test]
+    fn follows_the_calendar_across_dst() {
+        // New York moved its clocks forward on 2024-03-10.
+        let zone: Zone = "America/New_York".parse().unwrap();
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let diff = Diff::new(
+            at("2024-03-09T12:00:00-05:00"),
+            at("2024-03-10T12:00:00-04:00"),
+            zone,
+        );
+        assert_eq!(diff.hours, 23);
+        assert_eq!(diff.days, 0);
+        assert_eq!(diff.calendar, breakdown(0, 0, 1, 0));
+    }
+
+    #[test]
+    fn humanizes_with_a_granularity() {
+        let b = Breakdown {
+            minutes: 7,
+            seconds: 9,
+            ..breakdown(3, 2, 5, 4)
+        };
+        assert_eq!(b.humanize(1), "in 3 years");
+        assert_eq!(b.humanize(3), "in 3 years, 2 months, 5 days");
+        assert_eq!(
+            b.humanize(0),
+            "in 3 years, 2 months, 5 days, 4 hours, 7 minutes, 9 seconds"
+        );
+        // The cutoff counts from the largest unit that isn't zero, and zero
+        // units inside it are left out.
+        assert_eq!(breakdown(0, 0, 2, 0).humanize(2), "in 2 days");
+        assert_eq!(breakdown(0, 1, 0, 4).humanize(3), "in 1 month, 4 hours");
+    }
+
+    #[test]
+    fn humanizes_the_direction() {
+        assert_eq!(breakdown(0, -1, -1, 0).humanize(3), "1 month, 1 day ago");
+        assert_eq!(breakdown(0, 0, 0, 1).humanize(3), "in 1 hour");
+        assert_eq!(breakdown(0, 0, 0, 0).humanize(3), "now");
+        // Less than a second is still now.
+        let b = Breakdown {
+            nanoseconds: 5,
+            ..breakdown(0, 0, 0, 0)
+        };
+        assert_eq!(b.humanize(0), "now");
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt;
+
+use chrono::Duration;
+use serde::Serialize;
+
+use crate::diff::Breakdown;
+use crate::report::decimal_seconds;
+use crate::span::Span;
+
+type Parser = fn(&str) -> Result<Span, String>;
+
+/// The duration notations `parse_duration` understands, in the order they are
+/// tried.
+const FORMATS: &[(&str, Parser)] = &[
+    ("ISO 8601", Span::parse_iso8601),
+    ("H:MM:SS", Span::parse_clock),
+    ("units", Span::parse),
+];
+
+/// Parses a length of time written in any of the common notations:
+///
+/// - Go and systemd style units, e.g. `1h30m`, `90061s` or `2 days 3 hours`
+/// - ISO 8601, e.g. `PT36H` or `P1DT12H`
+/// - clock style, e.g. `01:30:00`
+///
+/// A leading `-` makes the duration negative. Returns the span and the name of
+/// the notation it was written in; on failure, the error explains why each
+/// notation rejected the input.
+pub fn parse_duration(s: &str) -> Result<(Span, &'static str), String> {
+    let (negative, body) = match s.trim().strip_prefix('-') {
+        Some(body) => (true, body),
+        None => (false, s.trim()),
+    };
+    let mut error = format!("Unable to parse duration {}", s);
+    for &(name, parse) in FORMATS {
+        match parse(body) {
+            Ok(span) if negative => return Ok((span.negate(), name)),
+            Ok(span) => return Ok((span, name)),
+            Err(e) => error.push_str(&format!("\n  {:27} {}", name, e)),
+        }
+    }
+    Err(error)
+}
+
+/// A length of time converted to each unit `time-cli` knows.
+#[derive(Debug, Clone, Serialize)]
+pub struct DurationReport {
+    /// The notation the input was written in.
+    pub format: String,
+    pub seconds: i64,
+    pub seconds_float: f64,
+    pub milliseconds: i64,
+    pub nanoseconds: i128,
+    /// The same length in days, hours, minutes and seconds.
+    pub breakdown: Breakdown,
+}
+
+impl DurationReport {
+    /// Fails if the span has months or years, which have no fixed length.
+    pub fn new(span: Span, format: &str) -> Result<DurationReport, String> {
+        let d = span.to_exact().ok_or_else(|| {
+            "months and years have no fixed length, so they can't be converted".to_string()
+        })?;
+        let nanoseconds = d.num_seconds() as i128 * 1_000_000_000
+            + (d - Duration::seconds(d.num_seconds()))
+                .num_nanoseconds()
+                .unwrap_or(0) as i128;
+        Ok(DurationReport {
+            format: format.to_string(),
+            seconds: d.num_seconds(),
+            seconds_float: nanoseconds as f64 / 1e9,
+            milliseconds: d.num_milliseconds(),
+            nanoseconds,
+            breakdown: Breakdown::from_duration(d),
+        })
+    }
+}
+
+impl fmt::Display for DurationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:20}{}", "Parsed as:", self.format)?;
+        writeln!(f)?;
+        writeln!(f, "{:20}{}", "Seconds:", self.seconds)?;
+        writeln!(
+            f,
+            "{:20}{}",
+            "Seconds (float):",
+            decimal_seconds(self.nanoseconds)
+        )?;
+        writeln!(f, "{:20}{}", "Milliseconds:", self.milliseconds)?;
+        writeln!(f, "{:20}{}", "Nanoseconds:", self.nanoseconds)?;
+        writeln!(f)?;
+        writeln!(f, "{:20}{}", "Breakdown:", self.breakdown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_the_notation() {
+        assert_eq!(parse_duration("PT36H").map(|(_, f)| f), Ok("ISO 8601"));
+        assert_eq!(parse_duration("01:30:00").map(|(_, f)| f), Ok("H:MM:SS"));
+        assert_eq!(parse_duration("1h30m").map(|(_, f)| f), Ok("units"));
+        assert!(parse_duration("soon").is_err());
+    }
+
+    #[test]
+    fn converts_between_units() {
+        let (span, format) = parse_duration("-1h30m").unwrap();
+        let report = DurationReport::new(span, format).unwrap();
+        assert_eq!(report.seconds, -5400);
+        assert_eq!(report.milliseconds, -5_400_000);
+        assert_eq!(report.nanoseconds, -5_400_000_000_000);
+        assert_eq!(report.breakdown.to_string(), "minus 1 hour, 30 minutes");
+
+        let (span, format) = parse_duration("P1DT0.25S").unwrap();
+        let report = DurationReport::new(span, format).unwrap();
+        assert_eq!(report.seconds, 86400);
+        assert_eq!(report.nanoseconds, 86_400_250_000_000);
+    }
+
+    #[test]
+    fn rejects_months() {
+        let (span, format) = parse_duration("P1M").unwrap();
+        assert!(DurationReport::new(span, format).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+
+/// The Unix times at which each leap second since the GPS epoch took effect,
+/// i.e. the midnight after the inserted `23:59:60`.
+const LEAP_SECONDS: &[i64] = &[
+    362_793_600,   // 1981-07-01
+    394_329_600,   // 1982-07-01
+    425_865_600,   // 1983-07-01
+    489_024_000,   // 1985-07-01
+    567_993_600,   // 1988-01-01
+    631_152_000,   // 1990-01-01
+    662_688_000,   // 1991-01-01
+    709_948_800,   // 1992-07-01
+    741_484_800,   // 1993-07-01
+    773_020_800,   // 1994-07-01
+    820_454_400,   // 1996-01-01
+    867_715_200,   // 1997-07-01
+    915_148_800,   // 1999-01-01
+    1_136_073_600, // 2006-01-01
+    1_230_768_000, // 2009-01-01
+    1_341_100_800, // 2012-07-01
+    1_435_708_800, // 2015-07-01
+    1_483_228_800, // 2017-01-01
+];
+
+/// A numbering of instants other than Unix time, counting ticks from some
+/// other starting point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Epoch {
+    /// Windows FILETIME, also used for Active Directory timestamps like
+    /// `lastLogonTimestamp`: 100ns ticks since 1601-01-01 UTC.
+    #[serde(rename = "filetime")]
+    FileTime,
+    /// .NET `DateTime.Ticks`: 100ns ticks since 0001-01-01 UTC.
+    #[serde(rename = "dotnet")]
+    DotNet,
+    /// Apple Cocoa and Core Data absolute time: seconds since 2001-01-01 UTC.
+    #[serde(rename = "cocoa")]
+    Cocoa,
+    /// GPS time: seconds since 1980-01-06 UTC, counting the leap seconds
+    /// since, so it runs ahead of UTC.
+    #[serde(rename = "gps")]
+    Gps,
+    /// HFS+ dates: seconds since 1904-01-01 UTC.
+    #[serde(rename = "hfs")]
+    HfsPlus,
+}
+
+impl Epoch {
+    pub const ALL: [Epoch; 5] = [
+        Epoch::FileTime,
+        Epoch::DotNet,
+        Epoch::Cocoa,
+        Epoch::Gps,
+        Epoch::HfsPlus,
+    ];
+
+    /// The names of `ALL`, as accepted by `from_str`.
+    pub const NAMES: &'static [&'static str] = &["filetime", "dotnet", "cocoa", "gps", "hfs"];
+
+    /// The Unix time of the epoch, in seconds.
+    fn origin(self) -> i64 {
+        match self {
+            Epoch::FileTime => -11_644_473_600,
+            Epoch::DotNet => -62_135_596_800,
+            Epoch::Cocoa => 978_307_200,
+            Epoch::Gps => 315_964_800,
+            Epoch::HfsPlus => -2_082_844_800,
+        }
+    }
+
+    /// How many nanoseconds one tick is.
+    pub fn tick_nanos(self) -> i128 {
+        match self {
+            Epoch::FileTime | Epoch::DotNet => 100,
+            Epoch::Cocoa | Epoch::Gps | Epoch::HfsPlus => NANOS_PER_SECOND,
+        }
+    }
+
+    /// Whether values are commonly written in hex, e.g. `0x01DA1B2C3D4E5F60`.
+    pub fn allows_hex(self) -> bool {
+        match self {
+            Epoch::FileTime => true,
+            Epoch::DotNet | Epoch::Cocoa | Epoch::Gps | Epoch::HfsPlus => false,
+        }
+    }
+
+    /// The name used on the command line and in machine-readable output.
+    pub fn name(self) -> &'static str {
+        match self {
+            Epoch::FileTime => "filetime",
+            Epoch::DotNet => "dotnet",
+            Epoch::Cocoa => "cocoa",
+            Epoch::Gps => "gps",
+            Epoch::HfsPlus => "hfs",
+        }
+    }
+
+    /// Converts nanoseconds since this epoch into nanoseconds since the Unix
+    /// epoch, or `None` on overflow.
+    pub fn to_unix_nanos(self, nanos: i128) -> Option<i128> {
+        let unix_nanos = nanos.checked_add(self.origin() as i128 * NANOS_PER_SECOND)?;
+        if self != Epoch::Gps {
+            return Some(unix_nanos);
+        }
+        // The n-th leap second put GPS time n seconds ahead of UTC.
+        let leaps = LEAP_SECONDS
+            .iter()
+            .zip(1..)
+            .filter(|&(&t, n)| unix_nanos >= (t + n) as i128 * NANOS_PER_SECOND)
+            .count();
+        unix_nanos.checked_sub(leaps as i128 * NANOS_PER_SECOND)
+    }
+
+    /// Converts nanoseconds since the Unix epoch into nanoseconds since this
+    /// epoch, or `None` on overflow.
+    pub fn from_unix_nanos(self, unix_nanos: i128) -> Option<i128> {
+        let nanos = unix_nanos.checked_sub(self.origin() as i128 * NANOS_PER_SECOND)?;
+        if self != Epoch::Gps {
+            return Some(nanos);
+        }
+        let leaps = LEAP_SECONDS
+            .iter()
+            .filter(|&&t| unix_nanos >= t as i128 * NANOS_PER_SECOND)
+            .count();
+        nanos.checked_add(leaps as i128 * NANOS_PER_SECOND)
+    }
+
+    /// The number of whole ticks since this epoch, rounding down, or `None`
+    /// on overflow.
+    pub fn ticks(self, unix_nanos: i128) -> Option<i128> {
+        Some(
+            self.from_unix_nanos(unix_nanos)?
+                .div_euclid(self.tick_nanos()),
+        )
+    }
+}
+
+impl fmt::Display for Epoch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Epoch::FileTime => "Windows FILETIME",
+            Epoch::DotNet => ".NET ticks",
+            Epoch::Cocoa => "Cocoa absolute time",
+            Epoch::Gps => "GPS time",
+            Epoch::HfsPlus => "HFS+ time",
+        })
+    }
+}
+
+impl FromStr for Epoch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Epoch, String> {
+        Epoch::ALL
+            .iter()
+            .cloned()
+            .find(|e| e.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "unknown epoch {}, expected one of {}",
+                    s,
+                    Epoch::NAMES.join(", ")
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NANOS: i128 = NANOS_PER_SECOND;
+
+    #[test]
+    fn epochs_start_at_their_origin() {
+        assert_eq!(
+            Epoch::FileTime.to_unix_nanos(0),
+            Some(-11_644_473_600 * NANOS)
+        );
+        assert_eq!(
+            Epoch::DotNet.to_unix_nanos(0),
+            Some(-62_135_596_800 * NANOS)
+        );
+        assert_eq!(Epoch::Cocoa.to_unix_nanos(0), Some(978_307_200 * NANOS));
+        assert_eq!(Epoch::Gps.to_unix_nanos(0), Some(315_964_800 * NANOS));
+        assert_eq!(
+            Epoch::HfsPlus.to_unix_nanos(0),
+            Some(-2_082_844_800 * NANOS)
+        );
+    }
+
+    #[test]
+    fn converts_known_values() {
+        // 2023-11-20T08:00:00Z
+        let unix = 1_700_467_200 * NANOS;
+        assert_eq!(Epoch::DotNet.ticks(unix), Some(638_360_640_000_000_000));
+        assert_eq!(Epoch::FileTime.ticks(unix), Some(133_449_408_000_000_000));
+        assert_eq!(Epoch::Cocoa.ticks(unix), Some(722_160_000));
+        assert_eq!(Epoch::HfsPlus.ticks(unix), Some(3_783_312_000));
+    }
+
+    #[test]
+    fn gps_counts_leap_seconds() {
+        // 2016-12-31T23:59:59Z, then the leap second, then 2017-01-01.
+        let before = 1_483_228_799 * NANOS;
+        let after = 1_483_228_800 * NANOS;
+        assert_eq!(Epoch::Gps.ticks(before), Some(1_167_264_016));
+        assert_eq!(Epoch::Gps.ticks(after), Some(1_167_264_018));
+        assert_eq!(
+            Epoch::Gps.to_unix_nanos(1_167_264_016 * NANOS),
+            Some(before)
+        );
+        assert_eq!(Epoch::Gps.to_unix_nanos(1_167_264_018 * NANOS), Some(after));
+        // The leap second itself has no Unix time of its own.
+        assert_eq!(Epoch::Gps.to_unix_nanos(1_167_264_017 * NANOS), Some(after));
+        // Before the first leap second, GPS and UTC agree.
+        assert_eq!(Epoch::Gps.ticks(315_964_800 * NANOS), Some(0));
+    }
+
+    #[test]
+    fn overflow_is_none() {
+        for &epoch in &Epoch::ALL {
+            if epoch.origin() > 0 {
+                assert_eq!(epoch.to_unix_nanos(i128::MAX), None, "{}", epoch);
+                assert_eq!(epoch.from_unix_nanos(i128::MIN), None, "{}", epoch);
+            } else {
+                assert_eq!(epoch.to_unix_nanos(i128::MIN), None, "{}", epoch);
+                assert_eq!(epoch.from_unix_nanos(i128::MAX), None, "{}", epoch);
+            }
+        }
+    }
+}
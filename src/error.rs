@@ -0,0 +1,103 @@
+use std::fmt;
+use std::num::{ParseFloatError, ParseIntError};
+
+use chrono::format;
+
+/// Why a single candidate format rejected the input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reason {
+    /// chrono rejected the input. `field` names the first field of the format
+    /// that could not be filled in, when that is known, e.g. `month` for
+    /// `2024-13-01`.
+    Format {
+        error: format::ParseError,
+        field: Option<&'static str>,
+    },
+    /// The input is not an integer.
+    Int(ParseIntError),
+    /// The input is not a floating-point number.
+    Float(ParseFloatError),
+    /// The input isn't a valid expression in one of the small grammars, like
+    /// `3 days ago`. Holds a description of the problem.
+    Expression(String),
+    /// The input doesn't end in a time zone.
+    MissingZone,
+    /// The input ends in something that isn't a known time zone.
+    UnknownZone(String),
+    /// The input describes a valid instant, but it is outside the range of
+    /// times `time-cli` is willing to guess at. Holds the offending value in
+    /// Unix seconds.
+    OutOfBounds(i64),
+    /// Like `OutOfBounds`, but the instant is too far from 1970 to have a Unix
+    /// time at all, e.g. `inf` or a span of a million years.
+    Unrepresentable,
+}
+
+impl Reason {
+    /// Whether the input was understood, but the instant it described was
+    /// outside the supported range.
+    pub fn is_out_of_bounds(&self) -> bool {
+        matches!(*self, Reason::OutOfBounds(_) | Reason::Unrepresentable)
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Reason::Format {
+                ref error,
+                field: Some(field),
+            } => write!(f, "{} (at {})", error, field),
+            Reason::Format { ref error, .. } => write!(f, "{}", error),
+            Reason::Int(ref e) => write!(f, "not an integer: {}", e),
+            Reason::Float(ref e) => write!(f, "not a number: {}", e),
+            Reason::Expression(ref e) => write!(f, "{}", e),
+            Reason::MissingZone => write!(f, "no trailing time zone"),
+            Reason::UnknownZone(ref zone) => write!(f, "unknown time zone {}", zone),
+            Reason::OutOfBounds(ts) => write!(
+                f,
+                "unix time {} is outside the supported range (1900 to 2500)",
+                ts
+            ),
+            Reason::Unrepresentable => write!(
+                f,
+                "too far in the past or future to represent (the supported range is 1900 to 2500)"
+            ),
+        }
+    }
+}
+
+/// A rejection from one of the candidate formats tried by `parse_with`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attempt {
+    pub format: String,
+    pub reason: Reason,
+}
+
+/// Returned when none of the candidate formats accept the input. Records why
+/// each candidate was rejected, in the order they were tried.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub input: String,
+    pub attempts: Vec<Attempt>,
+}
+
+impl ParseError {
+    /// Whether some candidate format understood the input, but the instant it
+    /// described was outside the supported range.
+    pub fn is_out_of_bounds(&self) -> bool {
+        self.attempts.iter().any(|a| a.reason.is_out_of_bounds())
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unable to parse timestamp {}", self.input)?;
+        for attempt in &self.attempts {
+            write!(f, "\n  {:27} {}", attempt.format, attempt.reason)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,138 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::parse::ParsedTime;
+use crate::unit::Unit;
+
+/// One way of reading an ambiguous input.
+#[derive(Debug, Clone, Serialize)]
+pub struct Interpretation {
+    /// 1 for the most plausible interpretation, i.e. the closest to now.
+    pub rank: usize,
+    pub format: String,
+    pub unit: Option<Unit>,
+    /// Whether this is the interpretation `parse` would pick.
+    pub default: bool,
+    pub unix: i64,
+    pub rfc3339_utc: String,
+    /// Negative for times in the past.
+    pub seconds_from_now: i64,
+}
+
+/// Every distinct instant an input could refer to, most plausible first.
+///
+/// All candidates already reject times outside 1900 to 2500, so plausibility
+/// is just closeness to now. When several formats produce the same instant,
+/// only the first one tried is kept.
+#[derive(Debug, Clone, Serialize)]
+pub struct Explanation {
+    pub input: String,
+    pub interpretations: Vec<Interpretation>,
+}
+
+impl Explanation {
+    /// `parsed` must be in the order the candidates were tried, as returned by
+    /// `parse_all`.
+    pub fn new(input: &str, parsed: Vec<ParsedTime>, now: DateTime<Utc>) -> Explanation {
+        let mut distinct: Vec<ParsedTime> = vec![];
+        for p in parsed {
+            if !distinct.iter().any(|d| d.time == p.time) {
+                distinct.push(p);
+            }
+        }
+
+        let mut interpretations: Vec<_> = distinct
+            .into_iter()
+            .enumerate()
+            .map(|(i, p)| Interpretation {
+                rank: 0,
+                format: p.format,
+                unit: p.unit,
+                default: i == 0,
+                unix: p.time.timestamp(),
+                rfc3339_utc: p.time.to_rfc3339(),
+                seconds_from_now: (p.time - now).num_seconds(),
+            })
+            .collect();
+        interpretations.sort_by_key(|i| i.seconds_from_now.abs());
+        for (rank, i) in interpretations.iter_mut().enumerate() {
+            i.rank = rank + 1;
+        }
+
+        Explanation {
+            input: input.to_string(),
+            interpretations,
+        }
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Interpretations of {}, most plausible first (* is the default):",
+            self.input
+        )?;
+        writeln!(f)?;
+        for i in &self.interpretations {
+            writeln!(
+                f,
+                "{:>3} {} {:28}{:38}{:>+10} days",
+                i.rank,
+                if i.default { '*' } else { ' ' },
+                i.format,
+                i.rfc3339_utc,
+                i.seconds_from_now / 86400
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_all, parse_with, ParseOptions};
+
+    #[test]
+    fn ranks_distinct_readings_by_closeness_to_now() {
+        // Closer to the unix seconds reading than to the date it spells.
+        let now = DateTime::parse_from_rfc3339("2034-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let options = ParseOptions::new(now);
+        let parsed = parse_all("2024010212", &options).unwrap();
+        let tried = parsed.len();
+        let explanation = Explanation::new("2024010212", parsed, now);
+        let interpretations = &explanation.interpretations;
+
+        // The float candidates read the same instants as the integer ones.
+        assert!(interpretations.len() < tried);
+        for (i, a) in interpretations.iter().enumerate() {
+            for b in &interpretations[i + 1..] {
+                assert_ne!(a.rfc3339_utc, b.rfc3339_utc);
+            }
+            assert!(!a.format.ends_with("(float)"), "{}", a.format);
+        }
+
+        let ranks: Vec<_> = interpretations.iter().map(|i| i.rank).collect();
+        assert_eq!(ranks, (1..=interpretations.len()).collect::<Vec<_>>());
+        assert!(interpretations
+            .windows(2)
+            .all(|w| w[0].seconds_from_now.abs() <= w[1].seconds_from_now.abs()));
+        assert_eq!(interpretations[0].format, "unix seconds");
+        assert_eq!(interpretations[0].unit, Some(Unit::Seconds));
+
+        let defaults: Vec<_> = interpretations.iter().filter(|i| i.default).collect();
+        assert_eq!(defaults.len(), 1);
+        let default = defaults[0];
+        assert_eq!(
+            default.format,
+            parse_with("2024010212", &options).unwrap().format
+        );
+        assert_eq!(default.rank, 2);
+        assert_eq!(default.rfc3339_utc, "2024-01-02T12:00:00+00:00");
+    }
+}
@@ -0,0 +1,39 @@
+//! Heuristics for turning loosely-formatted timestamps into instants, and for
+//! building the report that `time-cli` prints about them.
+
+mod annotate;
+mod calendar;
+mod datemath;
+mod diff;
+mod duration;
+mod epoch;
+mod error;
+mod explain;
+mod natural;
+mod output;
+mod parse;
+mod relative;
+mod report;
+mod span;
+mod splunk;
+mod unit;
+mod zone;
+
+pub use crate::annotate::annotate_line;
+pub use crate::datemath::parse_date_math;
+pub use crate::diff::{Breakdown, Diff};
+pub use crate::duration::{parse_duration, DurationReport};
+pub use crate::epoch::Epoch;
+pub use crate::error::{Attempt, ParseError, Reason};
+pub use crate::explain::{Explanation, Interpretation};
+pub use crate::natural::parse_natural;
+pub use crate::output::{csv_header, csv_row, Output};
+pub use crate::parse::{
+    parse, parse_all, parse_with, Hint, ParseOptions, ParsedTime, LOWER_BOUND, UPPER_BOUND,
+};
+pub use crate::relative::parse_relative;
+pub use crate::report::{Formatted, Relative, Report, ReportOptions, ZoneTime};
+pub use crate::span::{add_months, Span};
+pub use crate::splunk::parse_splunk;
+pub use crate::unit::Unit;
+pub use crate::zone::{DstIssue, Zone};
@@ -1,107 +1,341 @@
-use std::str::FromStr;
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+use std::iter;
+use std::process;
 
-use chrono::{
-    format::{self, StrftimeItems},
-    DateTime, Local, NaiveDateTime, Utc,
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use serde::Serialize;
+
+use time_cli::{
+    annotate_line, csv_header, csv_row, parse_all, parse_duration, parse_with, Diff,
+    DurationReport, Epoch, Explanation, Hint, Output, ParseError, ParseOptions, ParsedTime, Report,
+    ReportOptions, Span, Unit, Zone,
 };
-use clap::{App, Arg};
-
-// 1900
-const LOWER_BOUND: i64 = -2208988800;
-// 2500
-const UPPER_BOUND: i64 = 16725225600;
-
-fn parse_i64(s: &str) -> Result<DateTime<Utc>, ()> {
-    match i64::from_str(s) {
-        Ok(ts) if ts < UPPER_BOUND && ts > LOWER_BOUND => Ok(DateTime::<Utc>::from_utc(
-            NaiveDateTime::from_timestamp(ts, 0),
-            Utc,
-        )),
-        Ok(ts) => Ok(DateTime::<Utc>::from_utc(
-            NaiveDateTime::from_timestamp(ts / 1000, (ts % 1000) as u32),
-            Utc,
-        )),
-        Err(_) => Err(()),
-    }
-}
-
-fn parse_f64(s: &str) -> Result<DateTime<Utc>, ()> {
-    match f64::from_str(s) {
-        Ok(ts) => {
-            let ts = if ts < UPPER_BOUND as f64 && ts > LOWER_BOUND as f64 {
-                (ts * 1000.).round() as i64
-            } else {
-                ts.round() as i64
-            };
-            Ok(DateTime::<Utc>::from_utc(
-                NaiveDateTime::from_timestamp(ts / 1000, (ts % 1000) as u32),
-                Utc,
-            ))
-        }
-        Err(_) => Err(()),
+
+/// The input was understood and the report was printed.
+const EXIT_OK: i32 = 0;
+/// None of the candidate formats could make sense of the input.
+const EXIT_PARSE_FAILURE: i32 = 1;
+/// The command line itself was invalid, e.g. an unknown option.
+const EXIT_USAGE: i32 = 2;
+/// The input looked like a timestamp, but it is outside the supported range.
+const EXIT_OUT_OF_RANGE: i32 = 3;
+
+const EXIT_STATUS_HELP: &str = "EXIT STATUS:
+    0    The input was parsed successfully
+    1    The input could not be parsed as a timestamp
+    2    The command line was invalid
+    3    The input looks like a timestamp, but is outside 1900 to 2500";
+
+/// Reports a parse failure and exits with the matching status.
+fn fail(e: &ParseError, matches: &ArgMatches) -> ! {
+    let status = if e.is_out_of_bounds() {
+        EXIT_OUT_OF_RANGE
+    } else {
+        EXIT_PARSE_FAILURE
+    };
+    exit_with(e, status, matches)
+}
+
+/// Prints `message` and the usage, unless asked to be quiet, and exits.
+fn exit_with<T: Display>(message: T, status: i32, matches: &ArgMatches) -> ! {
+    if !matches.is_present("quiet") {
+        eprintln!("{}", message);
+        eprintln!("{}", matches.usage());
     }
+    process::exit(status);
 }
 
-fn parse_dt_str(fmt: &'static str) -> impl Fn(&str) -> Result<DateTime<Utc>, ()> {
-    move |s| {
-        let mut p = format::Parsed::new();
-        format::parse(&mut p, s, StrftimeItems::new(fmt)).map_err(|_| ())?;
-        if p.hour_mod_12.is_none() {
-            p.set_hour(0).unwrap();
-        }
-        if p.minute.is_none() {
-            p.set_minute(0).unwrap();
-        }
-        if p.day.is_none() {
-            p.set_day(1).unwrap();
+/// Parses `s` with the options given on the command line, or exits.
+fn parse_or_fail(s: &str, options: &ParseOptions, matches: &ArgMatches) -> ParsedTime {
+    parse_with(s, options).unwrap_or_else(|e| fail(&e, matches))
+}
+
+/// Splits a `--format` value into the format and the zone named after its last
+/// `@`, if there is one, e.g. `%H:%M %Z@America/New_York`.
+fn format_spec(s: &str) -> Result<(String, Option<Zone>), String> {
+    let (format, zone) = match s.rsplit_once('@') {
+        Some((format, zone)) => match zone.parse::<Zone>() {
+            Ok(zone) => (format, Some(zone)),
+            Err(_) => (s, None),
+        },
+        None => (s, None),
+    };
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        return Err(format!("invalid format {:?}", format));
+    }
+    Ok((format.to_string(), zone))
+}
+
+fn report_options(matches: &ArgMatches) -> ReportOptions {
+    let zone = matches
+        .value_of("zone")
+        .map(|zone| zone.parse().expect("validated by clap"))
+        .unwrap_or(Zone::Named(chrono_tz::UTC));
+    ReportOptions {
+        formats: matches
+            .values_of("format")
+            .into_iter()
+            .flatten()
+            .map(|s| {
+                let (format, z) = format_spec(s).expect("validated by clap");
+                (format, z.unwrap_or(zone))
+            })
+            .collect(),
+        zones: matches
+            .values_of("tz")
+            .into_iter()
+            .flatten()
+            .map(|tz| tz.parse().expect("validated by clap"))
+            .collect(),
+        granularity: matches
+            .value_of("granularity")
+            .expect("has a default")
+            .parse()
+            .expect("validated by clap"),
+    }
+}
+
+/// The arguments shared by `add` and `sub`.
+fn arithmetic_subcommand<'a, 'b>(name: &str, about: &'a str) -> App<'a, 'b> {
+    SubCommand::with_name(name)
+        .about(about)
+        .setting(AppSettings::AllowNegativeNumbers)
+        .arg(
+            Arg::with_name("TIME")
+                .help("The time to start from")
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::with_name("DURATION")
+                .help("How far to move, e.g. 1d2h, 90m or \"1 month\"")
+                .required(true)
+                .index(2),
+        )
+        .arg(
+            Arg::with_name("exact")
+                .help(
+                    "Treat days as exactly 24 hours, even across a DST change, rather than \
+                     moving the date on the calendar of --zone",
+                )
+                .long("exact"),
+        )
+}
+
+fn parse_options(matches: &ArgMatches, now: DateTime<Utc>) -> ParseOptions {
+    let mut options = ParseOptions::new(now);
+    if let Some(unit) = matches.value_of("unit") {
+        options.hint = Hint::Unit(unit.parse::<Unit>().expect("validated by clap"));
+    } else if let Some(epoch) = matches.value_of("epoch") {
+        options.hint = Hint::Epoch(epoch.parse::<Epoch>().expect("validated by clap"));
+    } else if let Some(fmt) = matches.value_of("input-format") {
+        options.hint = Hint::Format(fmt.to_string());
+    }
+    if let Some(zone) = matches.value_of("zone") {
+        options.zone = zone.parse().expect("validated by clap");
+    }
+    options
+}
+
+/// A report in a batch, labelled with the input it came from.
+#[derive(Serialize)]
+struct Row<'a> {
+    input: &'a str,
+    #[serde(flatten)]
+    report: &'a Report,
+}
+
+/// Renders one line of a batch for a time that parsed.
+fn batch_line(input: &str, report: &Report, matches: &ArgMatches) -> String {
+    let row = Row { input, report };
+    if let Some(name) = matches.value_of("field") {
+        return report_field(report, name).unwrap_or_else(|| unknown_field(name, matches));
+    }
+    match (output(matches), report.formatted.is_empty()) {
+        (Output::Csv, _) => csv_row(&row),
+        (Output::Yaml, _) => Output::Yaml.serialize(&row).trim_end().to_string(),
+        (Output::Json, true) => serde_json::to_string(&row).expect("output is serializable"),
+        (Output::Json, false) => {
+            serde_json::to_string(&report.formatted).expect("output is serializable")
         }
-        if p.month.is_none() {
-            p.set_month(1).unwrap();
+        (_, true) => {
+            let name = matches.value_of("batch-format").expect("has a default");
+            report.field(name).expect("validated by clap")
         }
-        let dt = DateTime::<Utc>::from_utc(
-            p.to_naive_date()
-                .map_err(|_| ())?
-                .and_time(p.to_naive_time().map_err(|_| ())?),
-            Utc,
+        (_, false) => report
+            .formatted
+            .iter()
+            .map(|formatted| formatted.value.as_str())
+            .collect::<Vec<_>>()
+            .join("\t"),
+    }
+}
+
+/// Converts each input to one compact line, reporting failures on stderr and
+/// leaving their lines empty so the output still lines up with the input.
+/// With `--output csv`, a header comes first, and each line is a row; failed
+/// rows keep their input. With `--output json` or `yaml`, failed lines hold
+/// just the input.
+/// Returns the exit status: the worst of the failures, if there were any.
+fn batch<I>(
+    inputs: I,
+    parse_options: &ParseOptions,
+    options: &ReportOptions,
+    matches: &ArgMatches,
+) -> i32
+where
+    I: Iterator<Item = String>,
+{
+    let output = output(matches);
+    if let Output::Toml | Output::Env = output {
+        exit_with(
+            "this output format can only hold one time; use csv, json or yaml",
+            EXIT_USAGE,
+            matches,
         );
-        if dt.timestamp() > LOWER_BOUND && dt.timestamp() < UPPER_BOUND {
-            Ok(dt)
+    }
+    let quiet = matches.is_present("quiet");
+    let csv = output == Output::Csv && !matches.is_present("field");
+    let header = csv_header(&Row {
+        input: "",
+        report: &Report::new(parse_options.now, parse_options.now, options),
+    });
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    if csv && !quiet && writeln!(out, "{}", header).is_err() {
+        return EXIT_OK;
+    }
+
+    let mut status = EXIT_OK;
+    for input in inputs {
+        let input = input.trim();
+        let line = if input.is_empty() {
+            String::new()
         } else {
-            Err(())
+            match parse_with(input, parse_options) {
+                Ok(parsed) => {
+                    let report = Report::from_parsed(&parsed, parse_options.now, options);
+                    batch_line(input, &report, matches)
+                }
+                Err(e) => {
+                    if !quiet {
+                        eprintln!("Unable to parse timestamp {}", input);
+                    }
+                    status = status.max(if e.is_out_of_bounds() {
+                        EXIT_OUT_OF_RANGE
+                    } else {
+                        EXIT_PARSE_FAILURE
+                    });
+                    String::new()
+                }
+            }
+        };
+        if quiet {
+            continue;
+        }
+        let line = if line.is_empty() && !matches.is_present("field") {
+            // Keep just the input, as an empty line isn't a valid CSV row,
+            // JSON value or YAML document.
+            let failed = serde_json::json!({ "input": input });
+            match output {
+                Output::Csv => format!(
+                    "{}{}",
+                    csv_row(&failed),
+                    ",".repeat(header.split(',').count() - 1)
+                ),
+                // In place of the list of formatted values.
+                Output::Json if !options.formats.is_empty() => "null".to_string(),
+                Output::Json => failed.to_string(),
+                Output::Yaml => Output::Yaml.serialize(&failed).trim_end().to_string(),
+                _ => line,
+            }
+        } else {
+            line
+        };
+        if writeln!(out, "{}", line).is_err() {
+            // The reader went away, e.g. `| head`.
+            break;
         }
     }
+    status
 }
 
-fn parse(s: &str) -> Result<DateTime<Utc>, ()> {
-    // See if it's basic unix time
-    for p in [
-        &parse_dt_str("%Y") as &dyn Fn(&str) -> Result<_, _>,
-        &parse_dt_str("%Y%m"),
-        &parse_dt_str("%Y%m%d"),
-        &parse_dt_str("%Y%m%d%H"),
-        &parse_dt_str("%Y%m%d%H%M"),
-        &|ss| {
-            DateTime::parse_from_rfc2822(ss)
-                .map(|t| t.with_timezone(&Utc))
-                .map_err(|_| ())
-        },
-        &|ss| {
-            DateTime::parse_from_rfc3339(ss)
-                .map(|t| t.with_timezone(&Utc))
-                .map_err(|_| ())
+fn output(matches: &ArgMatches) -> Output {
+    matches
+        .value_of("output")
+        .expect("has a default")
+        .parse()
+        .expect("validated by clap")
+}
+
+/// Looks up a key of the JSON output, like `unix_ms`, as text. Nested keys
+/// are separated by dots, e.g. `since.days` or `zones.0.rfc3339`.
+fn field<T: Serialize>(value: &T, name: &str) -> Option<String> {
+    let json = serde_json::to_value(value).expect("output is serializable");
+    Some(
+        match json.pointer(&format!("/{}", name.replace('.', "/")))? {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Null => String::new(),
+            value => value.to_string(),
         },
-        &parse_dt_str("%Y-%m-%dT%H:%M:%S"),
-        &parse_dt_str("%Y-%m-%dT%H:%M"),
-        &parse_i64,
-        &parse_f64,
-    ]
-    .iter()
-    {
-        if let Ok(t) = p(s) {
-            return Ok(t);
+    )
+}
+
+/// Like `field`, but renders the report's own fields exactly, e.g. without
+/// rounding `unix_float`.
+fn report_field(report: &Report, name: &str) -> Option<String> {
+    report.field(name).or_else(|| field(report, name))
+}
+
+fn unknown_field(name: &str, matches: &ArgMatches) -> ! {
+    exit_with(
+        format!("unknown field {:?}; use a key of the JSON output", name),
+        EXIT_USAGE,
+        matches,
+    )
+}
+
+/// Prints the report, or only the custom renderings if any were asked for.
+fn print_report(report: &Report, matches: &ArgMatches) {
+    if let Some(name) = matches.value_of("field") {
+        let value = report_field(report, name).unwrap_or_else(|| unknown_field(name, matches));
+        if !matches.is_present("quiet") {
+            println!("{}", value);
         }
+        return;
+    }
+    if report.formatted.is_empty() {
+        return print(report, matches);
+    }
+    if matches.is_present("quiet") {
+        return;
+    }
+    match output(matches) {
+        Output::Text => {
+            for formatted in &report.formatted {
+                println!("{}", formatted.value);
+            }
+        }
+        output => print!("{}", output.serialize(&report.formatted)),
     }
-    Err(())
+}
+
+fn print<T: Display + Serialize>(value: &T, matches: &ArgMatches) {
+    if let Some(name) = matches.value_of("field") {
+        let value = field(value, name).unwrap_or_else(|| unknown_field(name, matches));
+        if !matches.is_present("quiet") {
+            println!("{}", value);
+        }
+        return;
+    }
+    if matches.is_present("quiet") {
+        return;
+    }
+    print!("{}", output(matches).render(value));
 }
 
 fn main() {
@@ -109,66 +343,316 @@ fn main() {
         .version("0.1")
         .author("Robert Ying <rbtying@aeturnalus.com>")
         .about("Command-line utility for parsing timestamps")
+        .after_help(EXIT_STATUS_HELP)
+        .setting(AppSettings::AllowNegativeNumbers)
         .arg(
             Arg::with_name("DATETIME")
-                .help("A time or date, e.g. a Unix timestamp")
+                .help(
+                    "A time or date, e.g. a Unix timestamp or \"3 days ago\". \
+                     Put -- first if it starts with a -, e.g. -- -2h. Given several, or - \
+                     to read them from stdin, prints one compact line per time",
+                )
                 .required(false)
+                .multiple(true)
                 .index(1),
-        );
-    let matches = app.get_matches();
-
-    let now = Utc::now();
-
-    let utc_ts = match matches.value_of("DATETIME") {
-        Some(s) => match parse(s) {
-            Ok(ts) => ts,
-            Err(()) => {
-                eprintln!("Unable to parse timestamp {}", s);
-                eprintln!("{}", matches.usage());
-                return;
-            }
-        },
-        None => now,
+        )
+        .arg(
+            Arg::with_name("output")
+                .help("Output format")
+                .short("o")
+                .long("output")
+                .takes_value(true)
+                .possible_values(Output::NAMES)
+                .default_value("text")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("unit")
+                .help("Treat inputs as numeric epochs in this unit, rather than guessing")
+                .long("unit")
+                .takes_value(true)
+                .possible_values(&["s", "ms", "us", "ns"])
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("epoch")
+                .help(
+                    "Treat inputs as counts of ticks since another epoch: filetime (Windows \
+                     and Active Directory), dotnet (.NET DateTime.Ticks), cocoa (Cocoa and \
+                     Core Data), gps or hfs (HFS+)",
+                )
+                .long("epoch")
+                .takes_value(true)
+                .possible_values(Epoch::NAMES)
+                .conflicts_with("unit")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("input-format")
+                .help("Parse inputs with this strftime-style format, rather than guessing")
+                .long("input-format")
+                .takes_value(true)
+                .conflicts_with_all(&["unit", "epoch"])
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("tz")
+                .help("Also show the time in this IANA time zone, e.g. America/New_York")
+                .long("tz")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .validator(|tz| tz.parse::<Tz>().map(|_| ()))
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("granularity")
+                .help(
+                    "How many units to use when describing how long ago the time was, \
+                     e.g. 2 for \"3 years, 2 months ago\"; 0 uses all of them",
+                )
+                .long("granularity")
+                .takes_value(true)
+                .default_value("3")
+                .validator(|n| n.parse::<usize>().map(|_| ()).map_err(|e| e.to_string()))
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("zone")
+                .help(
+                    "Time zone whose calendar is used for inputs like \"tomorrow\" or \
+                     \"end of week\", and that annotate shows times in [default: UTC]",
+                )
+                .long("zone")
+                .takes_value(true)
+                .validator(|zone| zone.parse::<Zone>().map(|_| ()))
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("format")
+                .help(
+                    "Print only the time in this strftime-style format, e.g. \"%Y/%m/%d %H:%M %Z\". \
+                     Add @ZONE to render it in another zone than --zone, e.g. %H:%M@Asia/Tokyo. \
+                     May be given several times",
+                )
+                .long("format")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .validator(|s| format_spec(&s).map(|_| ()))
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("field")
+                .help(
+                    "Print only this value, named by its key in the JSON output, e.g. unix_ms, \
+                     rfc3339_utc or since.days",
+                )
+                .long("field")
+                .takes_value(true)
+                .conflicts_with("format")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("batch-format")
+                .help("What to print for each time when given several, unless --field is given")
+                .long("batch-format")
+                .takes_value(true)
+                .possible_values(Report::FIELDS)
+                .default_value("rfc3339_utc"),
+        )
+        .arg(
+            Arg::with_name("explain")
+                .help("List every plausible interpretation of DATETIME, not just the default one")
+                .long("explain")
+                .alias("all")
+                .requires("DATETIME"),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .help("Print nothing; only report whether the input is valid via the exit status")
+                .short("q")
+                .long("quiet")
+                .global(true),
+        )
+        .subcommand(
+            SubCommand::with_name("diff")
+                .about("Show how far apart two times are, e.g. the start and end of an incident")
+                .setting(AppSettings::AllowNegativeNumbers)
+                .arg(
+                    Arg::with_name("FROM")
+                        .help("The earlier time")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("TO")
+                        .help("The later time; the difference is negative if it is before FROM")
+                        .required(true)
+                        .index(2),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("duration")
+                .about("Convert a duration like 1h30m, PT36H or 01:30:00 between units")
+                .setting(AppSettings::AllowNegativeNumbers)
+                .arg(
+                    Arg::with_name("DURATION")
+                        .help(
+                            "A duration in Go or systemd style units, ISO 8601 or H:MM:SS. \
+                             Put -- first if it starts with a -",
+                        )
+                        .required(true)
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("annotate")
+                .about(
+                    "Copy stdin to stdout, following each epoch in it by the time it stands \
+                     for in RFC3339",
+                )
+                .arg(
+                    Arg::with_name("replace")
+                        .help("Replace epochs with the time, rather than following them by it")
+                        .long("replace"),
+                ),
+        )
+        .subcommand(arithmetic_subcommand(
+            "add",
+            "Add a duration to a time, e.g. to find when a TTL expires",
+        ))
+        .subcommand(arithmetic_subcommand(
+            "sub",
+            "Subtract a duration from a time, e.g. to find a retention cutoff",
+        ));
+    let matches = match app.get_matches_safe() {
+        Ok(matches) => matches,
+        Err(e) if e.use_stderr() => {
+            eprintln!("{}", e.message);
+            process::exit(EXIT_USAGE);
+        }
+        Err(e) => {
+            println!("{}", e.message);
+            process::exit(EXIT_OK);
+        }
     };
+    let now = Utc::now();
 
-    println!("{:20}{:.03}", "Unix time:", utc_ts.timestamp());
-    println!(
-        "{:20}{:.03}",
-        "Unix time (float):",
-        utc_ts.timestamp_millis() as f64 / 1000.
-    );
-    println!("{:20}{}", "Unix time (ms):", utc_ts.timestamp_millis());
-    println!("");
-
-    if utc_ts < now {
-        let elapsed = now - utc_ts;
-        println!("{:20}{}", "Seconds since", elapsed.num_seconds());
-        if elapsed.num_hours() > 0 {
-            println!("{:20}{}", "Hours since", elapsed.num_hours());
+    match matches.subcommand() {
+        ("diff", Some(matches)) => {
+            let options = parse_options(matches, now);
+            let from = parse_or_fail(
+                matches.value_of("FROM").expect("required"),
+                &options,
+                matches,
+            );
+            let to = parse_or_fail(matches.value_of("TO").expect("required"), &options, matches);
+            print(&Diff::new(from.time, to.time, options.zone), matches);
+            return;
         }
-        if elapsed.num_days() > 0 {
-            println!("{:20}{}", "Days since", elapsed.num_days());
+        ("duration", Some(matches)) => {
+            let report = parse_duration(matches.value_of("DURATION").expect("required"))
+                .and_then(|(span, format)| DurationReport::new(span, format))
+                .unwrap_or_else(|e| exit_with(e, EXIT_PARSE_FAILURE, matches));
+            print(&report, matches);
+            return;
         }
-        println!("");
-    } else if utc_ts > now {
-        let until = utc_ts - now;
-        println!("{:20}{}", "Seconds until", until.num_seconds());
-        if until.num_hours() > 0 {
-            println!("{:20}{}", "Hours until", until.num_hours());
+        ("annotate", Some(matches)) => {
+            let options = parse_options(matches, now);
+            let replace = matches.is_present("replace");
+            let (stdin, stdout) = (io::stdin(), io::stdout());
+            let (mut input, mut output) = (stdin.lock(), stdout.lock());
+            // Go a line at a time, flushing as we go, so this can sit behind
+            // `tail -f`. Logs aren't always valid UTF-8, so read bytes.
+            let mut line = vec![];
+            while let Ok(n) = input.read_until(b'\n', &mut line) {
+                if n == 0 {
+                    break;
+                }
+                let annotated = annotate_line(&String::from_utf8_lossy(&line), &options, replace);
+                if output
+                    .write_all(annotated.as_bytes())
+                    .and_then(|()| output.flush())
+                    .is_err()
+                {
+                    // The reader went away, e.g. `| head`.
+                    break;
+                }
+                line.clear();
+            }
+            return;
         }
-        if until.num_days() > 0 {
-            println!("{:20}{}", "Days until", until.num_days());
+        (name @ "add", Some(matches)) | (name @ "sub", Some(matches)) => {
+            let options = parse_options(matches, now);
+            let base = parse_or_fail(
+                matches.value_of("TIME").expect("required"),
+                &options,
+                matches,
+            );
+            let span = Span::parse(matches.value_of("DURATION").expect("required"))
+                .unwrap_or_else(|e| exit_with(e, EXIT_PARSE_FAILURE, matches));
+            let span = if name == "sub" { span.negate() } else { span };
+            let result = base
+                .add(span, options.zone, matches.is_present("exact"))
+                .unwrap_or_else(|e| {
+                    let status = if e.is_out_of_bounds() {
+                        EXIT_OUT_OF_RANGE
+                    } else {
+                        EXIT_USAGE
+                    };
+                    exit_with(e, status, matches)
+                });
+            print_report(
+                &Report::from_parsed(&result, now, &report_options(matches)),
+                matches,
+            );
+            return;
         }
-        println!("");
+        _ => {}
     }
 
-    println!("{:20}{}", "RFC2822 UTC:", utc_ts.to_rfc2822());
-    println!("{:20}{}", "RFC3339 UTC:", utc_ts.to_rfc3339());
-    println!("{:20}{}", "YMD UTC:", utc_ts.format("%Y%m%d"));
-    println!("{:20}{}", "YMDH UTC:", utc_ts.format("%Y%m%d%H"));
-    println!("");
+    let parse_options = parse_options(&matches, now);
+    let options = report_options(&matches);
 
-    let local_ts = utc_ts.with_timezone(&Local);
-    println!("{:20}{}", "RFC2822 Local:", local_ts.to_rfc2822());
-    println!("{:20}{}", "RFC3339 Local:", local_ts.to_rfc3339());
+    let inputs: Vec<&str> = matches
+        .values_of("DATETIME")
+        .into_iter()
+        .flatten()
+        .collect();
+    if inputs.len() > 1 || inputs == ["-"] {
+        if matches.is_present("explain") {
+            exit_with("--explain takes a single DATETIME", EXIT_USAGE, &matches);
+        }
+        let status = if inputs == ["-"] {
+            let stdin = io::stdin();
+            let mut stdin = stdin.lock();
+            // Read bytes, as a line that isn't valid UTF-8 shouldn't end the
+            // batch; it just fails to parse.
+            let lines = iter::from_fn(|| {
+                let mut line = vec![];
+                match stdin.read_until(b'\n', &mut line) {
+                    Ok(0) | Err(_) => None,
+                    Ok(_) => Some(String::from_utf8_lossy(&line).into_owned()),
+                }
+            });
+            batch(lines, &parse_options, &options, &matches)
+        } else {
+            let inputs = inputs.into_iter().map(str::to_string);
+            batch(inputs, &parse_options, &options, &matches)
+        };
+        process::exit(status);
+    }
+
+    match matches.value_of("DATETIME") {
+        Some(s) if matches.is_present("explain") => match parse_all(s, &parse_options) {
+            Ok(parsed) => print(&Explanation::new(s, parsed, now), &matches),
+            Err(e) => fail(&e, &matches),
+        },
+        Some(s) => {
+            let parsed = parse_or_fail(s, &parse_options, &matches);
+            print_report(&Report::from_parsed(&parsed, now, &options), &matches)
+        }
+        None => print_report(&Report::new(now, now, &options), &matches),
+    }
 }
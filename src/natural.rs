@@ -0,0 +1,261 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Weekday};
+
+use crate::calendar::Period;
+use crate::relative::parse_time_of_day;
+use crate::span::days_in_month;
+
+const WEEKDAYS: &[(&str, Weekday)] = &[
+    ("monday", Weekday::Mon),
+    ("tuesday", Weekday::Tue),
+    ("wednesday", Weekday::Wed),
+    ("thursday", Weekday::Thu),
+    ("friday", Weekday::Fri),
+    ("saturday", Weekday::Sat),
+    ("sunday", Weekday::Sun),
+];
+
+const MONTHS: &[&str] = &[
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// Accepts full weekday names and their three-letter abbreviations.
+fn parse_weekday(s: &str) -> Option<Weekday> {
+    WEEKDAYS
+        .iter()
+        .find(|&&(name, _)| s == name || s == &name[..3])
+        .map(|&(_, day)| day)
+}
+
+fn parse_period(s: &str) -> Option<Period> {
+    match s {
+        "day" => Some(Period::Day),
+        "week" => Some(Period::Week),
+        "month" => Some(Period::Month),
+        "quarter" => Some(Period::Quarter),
+        "year" => Some(Period::Year),
+        _ => None,
+    }
+}
+
+/// Splits off a leading `this`, `next` or `last`, returning how many periods
+/// to move and the rest of the phrase.
+fn parse_modifier(s: &str) -> (i64, &str) {
+    for &(word, n) in &[("this ", 0), ("next ", 1), ("last ", -1)] {
+        if let Some(rest) = s.strip_prefix(word) {
+            return (n, rest.trim_start());
+        }
+    }
+    (0, s)
+}
+
+/// Parses `start of <period>` or `end of <period>`, where the period is
+/// optionally preceded by `this`, `next` or `last`. `today` is accepted as a
+/// period meaning the current day.
+fn parse_boundary(s: &str, today: NaiveDate) -> Option<Result<NaiveDateTime, String>> {
+    let (end, rest) = if let Some(rest) = s.strip_prefix("start of ") {
+        (false, rest)
+    } else if let Some(rest) = s.strip_prefix("beginning of ") {
+        (false, rest)
+    } else if let Some(rest) = s.strip_prefix("end of ") {
+        (true, rest)
+    } else {
+        return None;
+    };
+    let rest = rest.trim_start().trim_start_matches("the ");
+    let (n, period) = match rest {
+        "today" => (0, "day"),
+        _ => parse_modifier(rest),
+    };
+    let period = match parse_period(period) {
+        Some(period) => period,
+        None => return Some(Err(format!("unknown period {:?}", period))),
+    };
+    let start = period
+        .shift(
+            period.floor(today.and_hms(0, 0, 0)),
+            n + if end { 1 } else { 0 },
+        )
+        .ok_or_else(|| "date is out of range".to_string());
+    Some(start.map(|start| {
+        // The end of a period is its last whole second.
+        if end {
+            start - Duration::seconds(1)
+        } else {
+            start
+        }
+    }))
+}
+
+/// Parses `<ordinal> <weekday> of <month>`, e.g. `first monday of next month`,
+/// `last friday of this month` or `second tuesday of march`.
+fn parse_nth_weekday(s: &str, today: NaiveDate) -> Option<Result<NaiveDate, String>> {
+    let (ordinal, rest) = s.split_once(' ')?;
+    let nth: i64 = match ordinal {
+        "first" | "1st" => 1,
+        "second" | "2nd" => 2,
+        "third" | "3rd" => 3,
+        "fourth" | "4th" => 4,
+        "fifth" | "5th" => 5,
+        "last" => -1,
+        _ => return None,
+    };
+    let (weekday, month) = rest.split_once(" of ")?;
+    let weekday = parse_weekday(weekday.trim())?;
+
+    let month = month.trim();
+    let first = if let Some(m) = MONTHS.iter().position(|&name| name == month) {
+        NaiveDate::from_ymd(today.year(), m as u32 + 1, 1)
+    } else {
+        let (n, rest) = parse_modifier(month);
+        if rest != "month" {
+            return Some(Err(format!("unknown month {:?}", month)));
+        }
+        match Period::Month.shift(Period::Month.floor(today.and_hms(0, 0, 0)), n) {
+            Some(first) => first.date(),
+            None => return Some(Err("date is out of range".to_string())),
+        }
+    };
+
+    let date = if nth > 0 {
+        let offset = (7 + weekday.num_days_from_monday() as i64
+            - first.weekday().num_days_from_monday() as i64)
+            % 7;
+        first + Duration::days(offset + 7 * (nth - 1))
+    } else {
+        let last = first.with_day(days_in_month(first.year(), first.month())?)?;
+        let offset = (7 + last.weekday().num_days_from_monday() as i64
+            - weekday.num_days_from_monday() as i64)
+            % 7;
+        last - Duration::days(offset)
+    };
+    if date.month() != first.month() {
+        return Some(Err(format!(
+            "{} {} has no {} {}",
+            MONTHS[first.month0() as usize],
+            first.year(),
+            ordinal,
+            WEEKDAYS[weekday.num_days_from_monday() as usize].0
+        )));
+    }
+    Some(Ok(date))
+}
+
+/// Parses `next <weekday>`, `last <weekday>` or `this <weekday>`. `next` and
+/// `last` are the nearest such day strictly after or before today; `this` is
+/// the one in the current Monday-to-Sunday week.
+fn parse_weekday_phrase(s: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let (n, rest) = parse_modifier(s);
+    if rest.len() == s.len() {
+        return None;
+    }
+    let weekday = parse_weekday(rest)?;
+    let from = today.weekday().num_days_from_monday() as i64;
+    let to = weekday.num_days_from_monday() as i64;
+    let days = match n {
+        1 => (to - from + 6) % 7 + 1,
+        -1 => -((from - to + 6) % 7 + 1),
+        _ => to - from,
+    };
+    Some(today + Duration::days(days))
+}
+
+/// Parses calendar phrases relative to `today`, returning the wall-clock time
+/// they refer to:
+///
+/// - `next tuesday`, `last friday 17:00`, `this sunday`
+/// - `first monday of next month`, `last friday of march`
+/// - `start of week`, `end of quarter`, `start of next month`, `end of today`
+///
+/// A time of day may follow any phrase that names a day.
+pub fn parse_natural(s: &str, today: NaiveDate) -> Result<NaiveDateTime, String> {
+    let s = s.trim().to_lowercase();
+    if let Some(result) = parse_boundary(&s, today) {
+        return result;
+    }
+
+    // Peel off a trailing time of day, if there is one.
+    let (phrase, time) = match s.rsplit_once(' ') {
+        Some((phrase, time)) if time.contains(':') => (phrase, parse_time_of_day(time)?),
+        _ => (s.as_str(), parse_time_of_day("")?),
+    };
+    if let Some(date) = parse_nth_weekday(phrase, today) {
+        return date.map(|date| date.and_time(time));
+    }
+    if let Some(date) = parse_weekday_phrase(phrase, today) {
+        return Ok(date.and_time(time));
+    }
+    Err("not a calendar phrase".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(s: &str) -> Result<String, String> {
+        // A Friday.
+        let today = NaiveDate::from_ymd(2024, 3, 15);
+        parse_natural(s, today).map(|t| t.to_string())
+    }
+
+    #[test]
+    fn weekday_phrases() {
+        assert_eq!(eval("next tuesday"), Ok("2024-03-19 00:00:00".to_string()));
+        assert_eq!(eval("next friday"), Ok("2024-03-22 00:00:00".to_string()));
+        assert_eq!(eval("last friday"), Ok("2024-03-08 00:00:00".to_string()));
+        assert_eq!(eval("this sunday"), Ok("2024-03-17 00:00:00".to_string()));
+        assert_eq!(
+            eval("Last Fri 17:00"),
+            Ok("2024-03-08 17:00:00".to_string())
+        );
+    }
+
+    #[test]
+    fn nth_weekday_of_month() {
+        assert_eq!(
+            eval("first monday of next month"),
+            Ok("2024-04-01 00:00:00".to_string())
+        );
+        assert_eq!(
+            eval("last friday of march"),
+            Ok("2024-03-29 00:00:00".to_string())
+        );
+        assert_eq!(
+            eval("second tuesday of last month 09:30"),
+            Ok("2024-02-13 09:30:00".to_string())
+        );
+        assert!(eval("fifth monday of february").is_err());
+    }
+
+    #[test]
+    fn period_boundaries() {
+        assert_eq!(eval("start of week"), Ok("2024-03-11 00:00:00".to_string()));
+        assert_eq!(
+            eval("end of quarter"),
+            Ok("2024-03-31 23:59:59".to_string())
+        );
+        assert_eq!(
+            eval("start of next month"),
+            Ok("2024-04-01 00:00:00".to_string())
+        );
+        assert_eq!(eval("end of today"), Ok("2024-03-15 23:59:59".to_string()));
+        assert!(eval("start of fortnight").is_err());
+    }
+
+    #[test]
+    fn rejects_other_text() {
+        for s in &["", "tuesday", "next tuesdayé", "é", "first é of march"] {
+            assert!(eval(s).is_err(), "{}", s);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// The ways `time-cli` can print its results. All but `Text` are generated
+/// from the `Serialize` impls, so they share the JSON output's keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Output {
+    Text,
+    Json,
+    Yaml,
+    Toml,
+    /// A header line and one row, with a column for each top-level key.
+    /// Nested values are written as JSON.
+    Csv,
+    /// `TIME_<KEY>='value'` lines, suitable for `eval`. Nested keys are joined
+    /// with underscores, e.g. `TIME_SINCE_DAYS`.
+    Env,
+}
+
+impl Output {
+    pub const NAMES: &'static [&'static str] = &["text", "json", "yaml", "toml", "csv", "env"];
+
+    /// Renders `value`, ending in a newline.
+    pub fn render<T: Display + Serialize>(self, value: &T) -> String {
+        match self {
+            Output::Text => value.to_string(),
+            _ => self.serialize(value),
+        }
+    }
+
+    /// Renders `value` in this structured format, ending in a newline. `Text`
+    /// falls back to JSON, since there's no `Display` impl to use.
+    pub fn serialize<T: Serialize>(self, value: &T) -> String {
+        let json = || serde_json::to_value(value).expect("output is serializable");
+        match self {
+            Output::Text | Output::Json => format!(
+                "{}\n",
+                serde_json::to_string_pretty(value).expect("output is serializable")
+            ),
+            Output::Yaml => format!(
+                "{}\n",
+                serde_yaml::to_string(&json())
+                    .expect("output is serializable")
+                    .trim_end()
+            ),
+            Output::Toml => {
+                // TOML documents are tables, and plain values must come before
+                // nested tables, which `toml::Value` takes care of.
+                let json = match without_nulls(json()) {
+                    Value::Object(map) => Value::Object(map),
+                    value => serde_json::json!({ "value": value }),
+                };
+                let toml = toml::Value::try_from(json).expect("output is serializable");
+                toml::to_string(&toml).expect("output is serializable")
+            }
+            Output::Csv => format!("{}\n{}\n", csv_header(value), csv_row(value)),
+            Output::Env => flatten(&json())
+                .into_iter()
+                .map(|(key, value)| {
+                    format!(
+                        "TIME_{}='{}'\n",
+                        key.to_uppercase().replace('.', "_"),
+                        value.replace('\'', "'\\''")
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl FromStr for Output {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Output, String> {
+        match s {
+            "text" => Ok(Output::Text),
+            "json" => Ok(Output::Json),
+            "yaml" => Ok(Output::Yaml),
+            "toml" => Ok(Output::Toml),
+            "csv" => Ok(Output::Csv),
+            "env" => Ok(Output::Env),
+            _ => Err(format!("unknown output format {:?}", s)),
+        }
+    }
+}
+
+/// A leaf value as text: strings without quotes, nulls as empty strings, and
+/// anything nested as compact JSON.
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+/// Drops null values from objects, since TOML has no way to write them.
+fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, without_nulls(value)))
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(without_nulls).collect()),
+        value => value,
+    }
+}
+
+/// Flattens `value` into `(key, value)` pairs, joining nested keys with dots
+/// and indexing arrays, e.g. `zones.0.rfc3339`.
+fn flatten(value: &Value) -> Vec<(String, String)> {
+    fn walk(prefix: &str, value: &Value, out: &mut Vec<(String, String)>) {
+        let join = |key: &dyn Display| {
+            if prefix.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}.{}", prefix, key)
+            }
+        };
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    walk(&join(key), value, out);
+                }
+            }
+            Value::Array(values) => {
+                for (i, value) in values.iter().enumerate() {
+                    walk(&join(&i), value, out);
+                }
+            }
+            value => out.push((prefix.to_string(), text(value))),
+        }
+    }
+    let mut out = vec![];
+    walk("", value, &mut out);
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// The CSV header for `value`: its top-level keys. These don't depend on the
+/// time being reported, so every row of a batch shares the same header.
+pub fn csv_header<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value).expect("output is serializable") {
+        Value::Object(map) => map
+            .keys()
+            .map(|key| csv_field(key))
+            .collect::<Vec<_>>()
+            .join(","),
+        _ => "value".to_string(),
+    }
+}
+
+/// One CSV row for `value`, with the columns of `csv_header`.
+pub fn csv_row<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value).expect("output is serializable") {
+        Value::Object(map) => map
+            .values()
+            .map(|value| csv_field(&text(value)))
+            .collect::<Vec<_>>()
+            .join(","),
+        value => csv_field(&text(&value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn flattens_nested_keys_and_arrays() {
+        let value = json!({
+            "unix": 1,
+            "since": { "days": 2 },
+            "zones": [{ "name": "UTC" }, null],
+        });
+        assert_eq!(
+            flatten(&value),
+            vec![
+                ("unix".to_string(), "1".to_string()),
+                ("since.days".to_string(), "2".to_string()),
+                ("zones.0.name".to_string(), "UTC".to_string()),
+                ("zones.1".to_string(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn quotes_csv_fields_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn drops_nulls_at_every_depth() {
+        let value = json!({ "a": null, "b": { "c": null, "d": 1 }, "e": [{ "f": null }] });
+        assert_eq!(without_nulls(value), json!({ "b": { "d": 1 }, "e": [{}] }));
+    }
+
+    #[test]
+    fn toml_wraps_plain_values() {
+        assert_eq!(Output::Toml.serialize(&5), "value = 5\n");
+        assert_eq!(
+            Output::Toml.serialize(&json!({ "a": null, "b": "x" })),
+            "b = \"x\"\n"
+        );
+    }
+
+    #[test]
+    fn env_escapes_single_quotes() {
+        assert_eq!(
+            Output::Env.serialize(&json!({ "name": "it's", "since": { "days": 2 } })),
+            "TIME_NAME='it'\\''s'\nTIME_SINCE_DAYS='2'\n"
+        );
+    }
+
+    #[test]
+    fn csv_escapes_commas_and_quotes() {
+        let value = json!({ "text": "a,\"b\"", "nested": { "x": 1 } });
+        assert_eq!(
+            Output::Csv.serialize(&value),
+            "text,nested\n\"a,\"\"b\"\"\",\"{\"\"x\"\":1}\"\n"
+        );
+        assert_eq!(Output::Csv.serialize(&"a,b"), "value\n\"a,b\"\n");
+    }
+}
@@ -0,0 +1,704 @@
+use std::str::FromStr;
+
+use chrono::{
+    format::{self, Item, Numeric, StrftimeItems},
+    DateTime, Duration, NaiveDateTime, NaiveTime, Utc,
+};
+
+use crate::datemath::parse_date_math;
+use crate::epoch::Epoch;
+use crate::error::{Attempt, ParseError, Reason};
+use crate::natural::parse_natural;
+use crate::relative::parse_relative;
+use crate::span::Span;
+use crate::splunk::parse_splunk;
+use crate::unit::Unit;
+use crate::zone::{DstIssue, Zone};
+
+// 1900
+pub const LOWER_BOUND: i64 = -2208988800;
+// 2500
+pub const UPPER_BOUND: i64 = 16725225600;
+
+/// A successfully parsed timestamp, along with the candidate format that
+/// matched it. `unit` is set when the input was a numeric epoch, and `dst` when
+/// it was a wall-clock time that a DST change made ambiguous.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedTime {
+    pub time: DateTime<Utc>,
+    pub format: String,
+    pub unit: Option<Unit>,
+    pub dst: Option<DstIssue>,
+}
+
+impl ParsedTime {
+    /// Moves the time by `span`: on the calendar in `zone`, or, if `exact`, as
+    /// a fixed length of time with days of 24 hours. Fails if `exact` is set
+    /// and the span has months, which have no fixed length, or if the result
+    /// is out of bounds.
+    pub fn add(self, span: Span, zone: Zone, exact: bool) -> Result<ParsedTime, Reason> {
+        let (time, dst) = if exact {
+            let d = span.to_exact().ok_or_else(|| {
+                Reason::Expression(
+                    "months and years have no fixed length, so they can't be added exactly"
+                        .to_string(),
+                )
+            })?;
+            (self.time.checked_add_signed(d), None)
+        } else {
+            match span.add_in(self.time, zone) {
+                Some((time, dst)) => (Some(time), dst),
+                None => (None, None),
+            }
+        };
+        // Without a result, the span was too large to apply at all.
+        let time = check_bounds(time.ok_or(Reason::Unrepresentable)?)?;
+        Ok(ParsedTime { time, dst, ..self })
+    }
+}
+
+fn check_bounds(dt: DateTime<Utc>) -> Result<DateTime<Utc>, Reason> {
+    if dt.timestamp() > LOWER_BOUND && dt.timestamp() < UPPER_BOUND {
+        Ok(dt)
+    } else {
+        Err(Reason::OutOfBounds(dt.timestamp()))
+    }
+}
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+
+/// Converts a count of nanoseconds since the epoch into an in-bounds instant.
+fn from_nanos(nanos: i128) -> Result<DateTime<Utc>, Reason> {
+    let secs = nanos.div_euclid(NANOS_PER_SECOND);
+    if secs > i64::MAX as i128 || secs < i64::MIN as i128 {
+        return Err(Reason::Unrepresentable);
+    }
+    let secs = secs as i64;
+    let nsecs = nanos.rem_euclid(NANOS_PER_SECOND) as u32;
+    match NaiveDateTime::from_timestamp_opt(secs, nsecs) {
+        Some(dt) => check_bounds(DateTime::<Utc>::from_utc(dt, Utc)),
+        None => Err(Reason::OutOfBounds(secs)),
+    }
+}
+
+fn nanos_per(unit: Unit) -> i128 {
+    NANOS_PER_SECOND / unit.per_second() as i128
+}
+
+fn parse_i64(unit: Unit) -> impl Fn(&str) -> Result<DateTime<Utc>, Reason> {
+    move |s| {
+        let ts = i64::from_str(s).map_err(Reason::Int)?;
+        from_nanos(ts as i128 * nanos_per(unit))
+    }
+}
+
+/// Converts a decimal count of ticks, `per` nanoseconds each, into nanoseconds. Plain decimal
+/// notation is converted exactly, digit by digit, since an f64 can't hold a
+/// nanosecond-precision epoch; anything else (e.g. exponents) goes through f64.
+fn decimal_to_nanos(s: &str, per: i128) -> Result<i128, Reason> {
+    let ts = f64::from_str(s).map_err(Reason::Float)?;
+    if ts.is_nan() {
+        // `as` would turn it into 0, i.e. the epoch itself.
+        return Err(Reason::Expression("NaN is not a time".to_string()));
+    }
+
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let mut parts = digits.splitn(2, '.');
+    let whole = parts.next().unwrap_or("");
+    let frac = parts.next().unwrap_or("");
+    let is_plain = whole.len() <= 20
+        && whole.bytes().all(|b| b.is_ascii_digit())
+        && frac.bytes().all(|b| b.is_ascii_digit());
+    if !is_plain {
+        // `as` saturates, which `from_nanos` then reports as out of bounds,
+        // so anything added to the result must be checked for overflow.
+        return Ok((ts * per as f64) as i128);
+    }
+
+    let mut nanos = whole.parse::<i128>().unwrap_or(0) * per;
+    let mut scale = per;
+    for d in frac.bytes() {
+        scale /= 10;
+        nanos += (d - b'0') as i128 * scale;
+    }
+    Ok(if negative { -nanos } else { nanos })
+}
+
+fn parse_f64(unit: Unit) -> impl Fn(&str) -> Result<DateTime<Utc>, Reason> {
+    move |s| from_nanos(decimal_to_nanos(s, nanos_per(unit))?)
+}
+
+/// Converts `nanos` since `epoch` into an in-bounds instant.
+fn from_epoch_nanos(epoch: Epoch, nanos: i128) -> Result<DateTime<Utc>, Reason> {
+    match epoch.to_unix_nanos(nanos) {
+        Some(unix_nanos) => from_nanos(unix_nanos),
+        None => Err(Reason::Unrepresentable),
+    }
+}
+
+/// Parses a decimal count of ticks since `epoch`.
+fn parse_epoch(epoch: Epoch) -> impl Fn(&str) -> Result<DateTime<Utc>, Reason> {
+    move |s| from_epoch_nanos(epoch, decimal_to_nanos(s, epoch.tick_nanos())?)
+}
+
+/// Parses a `0x`-prefixed hex count of ticks since `epoch`.
+fn parse_epoch_hex(epoch: Epoch) -> impl Fn(&str) -> Result<DateTime<Utc>, Reason> {
+    move |s| {
+        let hex = s
+            .strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .ok_or_else(|| Reason::Expression("hex values start with 0x".to_string()))?;
+        let ticks = u64::from_str_radix(hex, 16).map_err(Reason::Int)?;
+        from_epoch_nanos(epoch, ticks as i128 * epoch.tick_nanos())
+    }
+}
+
+/// Finds the first numeric field in `fmt` that chrono did not manage to fill
+/// in, which is where parsing stopped.
+fn failed_field(p: &format::Parsed, fmt: &str) -> Option<&'static str> {
+    StrftimeItems::new(fmt).find_map(|item| {
+        let (name, set) = match item {
+            Item::Numeric(Numeric::Year, _) => ("year", p.year.is_some()),
+            Item::Numeric(Numeric::Month, _) => ("month", p.month.is_some()),
+            Item::Numeric(Numeric::Day, _) => ("day", p.day.is_some()),
+            Item::Numeric(Numeric::Hour, _) => ("hour", p.hour_mod_12.is_some()),
+            Item::Numeric(Numeric::Minute, _) => ("minute", p.minute.is_some()),
+            Item::Numeric(Numeric::Second, _) => ("second", p.second.is_some()),
+            _ => return None,
+        };
+        if set {
+            None
+        } else {
+            Some(name)
+        }
+    })
+}
+
+/// chrono only range-checks fields when assembling the date, so when that fails
+/// this finds the field that was out of range.
+fn invalid_field(p: &format::Parsed) -> Option<&'static str> {
+    let fields = [
+        ("month", p.month, 1, 12),
+        ("day", p.day, 1, 31),
+        (
+            "hour",
+            p.hour_mod_12.map(|h| h + 12 * p.hour_div_12.unwrap_or(0)),
+            0,
+            23,
+        ),
+        ("minute", p.minute, 0, 59),
+        ("second", p.second, 0, 60),
+    ];
+    fields
+        .iter()
+        .find(|&&(_, value, min, max)| matches!(value, Some(v) if v < min || v > max))
+        .map(|&(name, ..)| name)
+}
+
+/// Parses `s` with `fmt`, filling in any missing fields with the start of the
+/// year, and returns the wall-clock time along with its offset from UTC in
+/// seconds, if the format included one.
+fn parse_naive(fmt: &str, s: &str) -> Result<(NaiveDateTime, Option<i32>), Reason> {
+    let mut p = format::Parsed::new();
+    format::parse(&mut p, s, StrftimeItems::new(fmt)).map_err(|error| Reason::Format {
+        error,
+        field: failed_field(&p, fmt),
+    })?;
+    if p.timestamp.is_none() {
+        // A user-supplied format may set fields that conflict with these
+        // defaults, e.g. `%p` without an hour, so report rather than unwrap.
+        let fill = |result: format::ParseResult<()>| {
+            result.map_err(|error| Reason::Format { error, field: None })
+        };
+        if p.hour_mod_12.is_none() && p.hour_div_12.is_none() {
+            fill(p.set_hour(0))?;
+        }
+        if p.minute.is_none() {
+            fill(p.set_minute(0))?;
+        }
+        if p.day.is_none() {
+            fill(p.set_day(1))?;
+        }
+        if p.month.is_none() {
+            fill(p.set_month(1))?;
+        }
+    }
+    let local = p
+        .to_naive_datetime_with_offset(p.offset.unwrap_or(0))
+        .map_err(|error| Reason::Format {
+            error,
+            field: invalid_field(&p),
+        })?;
+    Ok((local, p.offset))
+}
+
+fn parse_dt_str<'a>(fmt: &'a str) -> impl Fn(&str) -> Result<DateTime<Utc>, Reason> + 'a {
+    move |s| {
+        let (local, offset) = parse_naive(fmt, s)?;
+        // Without an explicit offset in the format, assume UTC.
+        let offset = Duration::seconds(offset.unwrap_or(0) as i64);
+        check_bounds(DateTime::<Utc>::from_utc(local - offset, Utc))
+    }
+}
+
+/// Date-time formats accepted before a trailing time zone.
+const ZONED_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d",
+];
+
+/// Time-only formats accepted before a trailing time zone, which refer to
+/// today's date in that zone.
+const ZONED_TIME_FORMATS: &[&str] = &["%H:%M:%S", "%H:%M"];
+
+/// Parses a wall-clock time followed by an IANA zone name or abbreviation,
+/// e.g. `2024-03-10 02:30 America/Los_Angeles` or `12:00 PST`.
+fn parse_zoned(
+    now: DateTime<Utc>,
+) -> impl Fn(&str) -> Result<(DateTime<Utc>, Option<DstIssue>), Reason> {
+    move |s| {
+        let (wall, zone) = s
+            .trim()
+            .rsplit_once(char::is_whitespace)
+            .ok_or(Reason::MissingZone)?;
+        let zone = zone
+            .parse::<Zone>()
+            .map_err(|_| Reason::UnknownZone(zone.to_string()))?;
+        let wall = wall.trim_end();
+
+        let mut reason = Reason::MissingZone;
+        for fmt in ZONED_FORMATS {
+            match parse_naive(fmt, wall) {
+                Ok((naive, _)) => {
+                    let (time, dst) = zone.resolve(naive);
+                    return Ok((check_bounds(time)?, dst));
+                }
+                Err(r) => reason = r,
+            }
+        }
+        for fmt in ZONED_TIME_FORMATS {
+            if let Ok(t) = NaiveTime::parse_from_str(wall, fmt) {
+                let (time, dst) = zone.resolve(zone.today(now).and_time(t));
+                return Ok((check_bounds(time)?, dst));
+            }
+        }
+        Err(reason)
+    }
+}
+
+fn parse_rfc2822(s: &str) -> Result<DateTime<Utc>, Reason> {
+    DateTime::parse_from_rfc2822(s)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|error| Reason::Format { error, field: None })
+}
+
+fn parse_rfc3339(s: &str) -> Result<DateTime<Utc>, Reason> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|error| Reason::Format { error, field: None })
+}
+
+/// Narrows down which candidate formats `parse_with` tries.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Hint {
+    /// Try every built-in format, in order.
+    Guess,
+    /// The input is a numeric epoch in the given unit.
+    Unit(Unit),
+    /// The input is a count of ticks since another epoch, e.g. a Windows
+    /// FILETIME.
+    Epoch(Epoch),
+    /// The input matches this strftime-style format. Fields missing from the
+    /// format are filled in as in the built-in formats, and the time is taken
+    /// to be UTC unless the format includes an offset.
+    Format(String),
+}
+
+/// Everything besides the input itself that affects how it's parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseOptions {
+    pub hint: Hint,
+    /// The instant that relative inputs like `3 days ago` are measured from.
+    pub now: DateTime<Utc>,
+    /// The zone whose calendar is used for inputs like `tomorrow` or
+    /// `end of week`.
+    pub zone: Zone,
+}
+
+impl ParseOptions {
+    /// Guesses the format, measuring from `now` on the UTC calendar.
+    pub fn new(now: DateTime<Utc>) -> ParseOptions {
+        ParseOptions {
+            hint: Hint::Guess,
+            now,
+            zone: Zone::Named(chrono_tz::UTC),
+        }
+    }
+}
+
+type Parser<'a> = Box<dyn Fn(&str) -> Result<(DateTime<Utc>, Option<DstIssue>), Reason> + 'a>;
+
+/// One of the formats tried by `parse_with`.
+struct Candidate<'a> {
+    format: String,
+    unit: Option<Unit>,
+    parser: Parser<'a>,
+}
+
+impl<'a> Candidate<'a> {
+    fn new<F>(format: &str, parser: F) -> Candidate<'a>
+    where
+        F: Fn(&str) -> Result<DateTime<Utc>, Reason> + 'a,
+    {
+        Candidate::wall_clock(format, move |s| parser(s).map(|t| (t, None)))
+    }
+
+    /// For parsers of wall-clock times, which may hit a DST change.
+    fn wall_clock<F>(format: &str, parser: F) -> Candidate<'a>
+    where
+        F: Fn(&str) -> Result<(DateTime<Utc>, Option<DstIssue>), Reason> + 'a,
+    {
+        Candidate {
+            format: format.to_string(),
+            unit: None,
+            parser: Box::new(parser),
+        }
+    }
+
+    /// Tries this candidate on `s`.
+    fn parse(self, s: &str) -> Result<ParsedTime, Attempt> {
+        match (self.parser)(s) {
+            Ok((time, dst)) => Ok(ParsedTime {
+                time,
+                format: self.format,
+                unit: self.unit,
+                dst,
+            }),
+            Err(reason) => Err(Attempt {
+                format: self.format,
+                reason,
+            }),
+        }
+    }
+
+    fn int(unit: Unit) -> Candidate<'a> {
+        Candidate {
+            unit: Some(unit),
+            ..Candidate::new(&format!("unix {}", unit), parse_i64(unit))
+        }
+    }
+
+    fn float(unit: Unit) -> Candidate<'a> {
+        Candidate {
+            unit: Some(unit),
+            ..Candidate::new(&format!("unix {} (float)", unit), parse_f64(unit))
+        }
+    }
+
+    /// Candidates for counts of ticks since `epoch`.
+    fn epoch(epoch: Epoch) -> Vec<Candidate<'a>> {
+        let mut candidates = vec![Candidate::new(&epoch.to_string(), parse_epoch(epoch))];
+        if epoch.allows_hex() {
+            candidates.push(Candidate::new(
+                &format!("{} (hex)", epoch),
+                parse_epoch_hex(epoch),
+            ));
+        }
+        candidates
+    }
+}
+
+fn candidates(options: &ParseOptions) -> Vec<Candidate<'_>> {
+    let (now, zone) = (options.now, options.zone);
+    match options.hint {
+        Hint::Guess => {
+            let mut candidates = vec![];
+            for fmt in &["%Y", "%Y%m", "%Y%m%d", "%Y%m%d%H", "%Y%m%d%H%M"] {
+                candidates.push(Candidate::new(fmt, parse_dt_str(fmt)));
+            }
+            candidates.push(Candidate::new("RFC2822", parse_rfc2822));
+            candidates.push(Candidate::new("RFC3339", parse_rfc3339));
+            for fmt in &["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d"] {
+                candidates.push(Candidate::new(fmt, parse_dt_str(fmt)));
+            }
+            candidates.push(Candidate::wall_clock(
+                "date-time with time zone",
+                parse_zoned(now),
+            ));
+            let base_options = options.clone();
+            candidates.push(Candidate::wall_clock("time plus duration", move |s| {
+                // Split at the last operator, so `now + 1d - 2h` applies the
+                // durations from left to right.
+                let at =
+                    match (s.rfind(" + "), s.rfind(" - ")) {
+                        (Some(plus), Some(minus)) => plus.max(minus),
+                        (Some(at), None) | (None, Some(at)) => at,
+                        (None, None) => return Err(Reason::Expression(
+                            "expected a time, then + or - with spaces around it, then a duration"
+                                .to_string(),
+                        )),
+                    };
+                let (base, span) = (&s[..at], &s[at + 3..]);
+                let span = Span::parse(span).map_err(Reason::Expression)?;
+                let span = if s[at..].starts_with(" - ") {
+                    span.negate()
+                } else {
+                    span
+                };
+                let base = parse_with(base, &base_options).map_err(|_| {
+                    Reason::Expression(format!("unable to parse base time {:?}", base))
+                })?;
+                let moved = base.add(span, zone, false)?;
+                Ok((moved.time, moved.dst))
+            }));
+            // Date math comes before relative times so that `now-1M` is a
+            // month, as in Elasticsearch, rather than a minute.
+            let anchor_options = options.clone();
+            candidates.push(Candidate::wall_clock("date math", move |s| {
+                let (time, dst) = parse_date_math(s, now, zone, |anchor| {
+                    parse_with(anchor, &anchor_options)
+                        .map(|parsed| parsed.time)
+                        .map_err(|_| format!("unable to parse anchor date {:?}", anchor))
+                })
+                .map_err(Reason::Expression)?;
+                Ok((check_bounds(time)?, dst))
+            }));
+            candidates.push(Candidate::wall_clock("relative time", move |s| {
+                let (time, dst) = parse_relative(s, now, zone).map_err(Reason::Expression)?;
+                Ok((check_bounds(time)?, dst))
+            }));
+            candidates.push(Candidate::wall_clock("calendar phrase", move |s| {
+                let wall = parse_natural(s, zone.today(now)).map_err(Reason::Expression)?;
+                let (time, dst) = zone.resolve(wall);
+                Ok((check_bounds(time)?, dst))
+            }));
+            candidates.push(Candidate::wall_clock("splunk time modifier", move |s| {
+                let (time, dst) = parse_splunk(s, now, zone).map_err(Reason::Expression)?;
+                Ok((check_bounds(time)?, dst))
+            }));
+            candidates.extend(Unit::ALL.iter().map(|&unit| Candidate::int(unit)));
+            candidates.extend(Unit::ALL.iter().map(|&unit| Candidate::float(unit)));
+            // Plausible tick counts are almost always also plausible Unix
+            // nanoseconds, so these are mostly reached for hex input or when
+            // listing every interpretation.
+            candidates.extend(Epoch::ALL.iter().flat_map(|&epoch| Candidate::epoch(epoch)));
+            candidates
+        }
+        Hint::Unit(unit) => vec![Candidate::int(unit), Candidate::float(unit)],
+        Hint::Epoch(epoch) => Candidate::epoch(epoch),
+        Hint::Format(ref fmt) => vec![Candidate::new(fmt, parse_dt_str(fmt))],
+    }
+}
+
+/// Guess what kind of timestamp `s` is, trying each candidate format in turn
+/// and returning the first that succeeds.
+pub fn parse(s: &str) -> Result<ParsedTime, ParseError> {
+    parse_with(s, &ParseOptions::new(Utc::now()))
+}
+
+/// Like `parse`, but only tries the candidate formats allowed by the hint in
+/// `options`, and resolves relative inputs against its `now` and `zone`.
+pub fn parse_with(s: &str, options: &ParseOptions) -> Result<ParsedTime, ParseError> {
+    let mut attempts = vec![];
+    for candidate in candidates(options) {
+        match candidate.parse(s) {
+            Ok(parsed) => return Ok(parsed),
+            Err(attempt) => attempts.push(attempt),
+        }
+    }
+    Err(ParseError {
+        input: s.to_string(),
+        attempts,
+    })
+}
+
+/// Like `parse_with`, but returns every candidate that accepts `s`, in the
+/// order they were tried, rather than stopping at the first.
+pub fn parse_all(s: &str, options: &ParseOptions) -> Result<Vec<ParsedTime>, ParseError> {
+    let mut parsed = vec![];
+    let mut attempts = vec![];
+    for candidate in candidates(options) {
+        match candidate.parse(s) {
+            Ok(p) => parsed.push(p),
+            Err(attempt) => attempts.push(attempt),
+        }
+    }
+    if parsed.is_empty() {
+        Err(ParseError {
+            input: s.to_string(),
+            attempts,
+        })
+    } else {
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(hint: Hint) -> ParseOptions {
+        ParseOptions {
+            hint,
+            ..ParseOptions::new(Utc::now())
+        }
+    }
+
+    fn rfc3339(s: &str, hint: Hint) -> Result<String, ParseError> {
+        parse_with(s, &options(hint)).map(|p| p.time.to_rfc3339())
+    }
+
+    #[test]
+    fn input_format_without_an_hour_for_am_pm_is_an_error() {
+        let hint = Hint::Format("%Y %p".to_string());
+        assert!(rfc3339("2024 PM", hint).is_err());
+        let hint = Hint::Format("%Y-%m-%d %I %p".to_string());
+        assert_eq!(
+            rfc3339("2024-01-02 03 PM", hint).unwrap(),
+            "2024-01-02T15:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn keeps_sub_second_precision() {
+        assert_eq!(
+            rfc3339("1700000000123", Hint::Guess).unwrap(),
+            "2023-11-14T22:13:20.123+00:00"
+        );
+        assert_eq!(
+            rfc3339("-1700000000123", Hint::Unit(Unit::Milliseconds)).unwrap(),
+            "1916-02-18T01:46:39.877+00:00"
+        );
+        assert_eq!(
+            rfc3339("1700000000123456", Hint::Guess).unwrap(),
+            "2023-11-14T22:13:20.123456+00:00"
+        );
+        assert_eq!(
+            rfc3339("1700000000123456789", Hint::Guess).unwrap(),
+            "2023-11-14T22:13:20.123456789+00:00"
+        );
+        assert_eq!(
+            rfc3339("1700000000.123456789", Hint::Guess).unwrap(),
+            "2023-11-14T22:13:20.123456789+00:00"
+        );
+    }
+
+    #[test]
+    fn guesses_the_unit_from_the_number_of_digits() {
+        let cases = [
+            ("1700000000", Unit::Seconds),
+            ("1700000000123", Unit::Milliseconds),
+            ("1700000000123456", Unit::Microseconds),
+            ("1700000000123456789", Unit::Nanoseconds),
+        ];
+        for &(s, unit) in &cases {
+            let parsed = parse_with(s, &options(Hint::Guess)).unwrap();
+            assert_eq!(parsed.unit, Some(unit), "{}", s);
+            assert_eq!(parsed.time.timestamp(), 1_700_000_000, "{}", s);
+        }
+    }
+
+    #[test]
+    fn rejects_nan() {
+        for s in &["nan", "NaN", "-nan"] {
+            for hint in &[
+                Hint::Guess,
+                Hint::Unit(Unit::Seconds),
+                Hint::Epoch(Epoch::FileTime),
+            ] {
+                let e = rfc3339(s, hint.clone()).unwrap_err();
+                assert!(!e.is_out_of_bounds(), "{}", s);
+            }
+        }
+    }
+
+    #[test]
+    fn parses_trailing_zones() {
+        let now = DateTime::parse_from_rfc3339("2024-07-01T18:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let options = ParseOptions::new(now);
+        let parsed = parse_with("12:00 PST", &options).unwrap();
+        assert_eq!(parsed.time.to_rfc3339(), "2024-07-01T20:00:00+00:00");
+        let parsed = parse_with("2024-03-10 02:30 America/Los_Angeles", &options).unwrap();
+        assert_eq!(parsed.time.to_rfc3339(), "2024-03-10T10:30:00+00:00");
+        assert!(matches!(parsed.dst, Some(DstIssue::Skipped { .. })));
+    }
+
+    #[test]
+    fn parses_filetime() {
+        let hint = Hint::Epoch(Epoch::FileTime);
+        assert_eq!(
+            rfc3339("133476480000000000", hint.clone()).unwrap(),
+            "2023-12-21T16:00:00+00:00"
+        );
+        assert_eq!(
+            rfc3339("0x01DA3426C0450000", hint).unwrap(),
+            "2023-12-21T16:00:00+00:00"
+        );
+        assert_eq!(
+            rfc3339("0x01DA3426C0450000", Hint::Guess).unwrap(),
+            "2023-12-21T16:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn huge_filetimes_are_out_of_bounds() {
+        for s in &["-inf", "-1e39", "-999999999999999999999999999999.5"] {
+            for hint in &[Hint::Guess, Hint::Epoch(Epoch::FileTime)] {
+                let e = rfc3339(s, hint.clone()).unwrap_err();
+                assert!(e.is_out_of_bounds(), "{}", s);
+            }
+        }
+    }
+
+    #[test]
+    fn huge_positive_tick_counts_are_out_of_bounds() {
+        for s in &["inf", "1e39", "999999999999999999999999999999.5"] {
+            for hint in &[
+                Hint::Guess,
+                Hint::Epoch(Epoch::Cocoa),
+                Hint::Epoch(Epoch::Gps),
+            ] {
+                let e = rfc3339(s, hint.clone()).unwrap_err();
+                assert!(e.is_out_of_bounds(), "{}", s);
+            }
+        }
+    }
+
+    #[test]
+    fn adds_spans() {
+        let base = parse_with("2024-01-31", &options(Hint::Guess)).unwrap();
+        let zone = Zone::Named(chrono_tz::UTC);
+        let month = Span::parse("1mo").unwrap();
+        assert_eq!(
+            base.clone()
+                .add(month, zone, false)
+                .unwrap()
+                .time
+                .to_rfc3339(),
+            "2024-02-29T00:00:00+00:00"
+        );
+        assert!(base.clone().add(month, zone, true).is_err());
+        let far = Span::parse("1000y").unwrap();
+        assert_eq!(
+            base.clone().add(far, zone, false).unwrap_err(),
+            Reason::OutOfBounds(33_263_568_000)
+        );
+        let too_far = Span::parse("300000y").unwrap();
+        assert_eq!(
+            base.clone().add(too_far, zone, false).unwrap_err(),
+            Reason::Unrepresentable
+        );
+        assert_eq!(
+            base.add(too_far.negate(), zone, false).unwrap_err(),
+            Reason::Unrepresentable
+        );
+    }
+}
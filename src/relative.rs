@@ -0,0 +1,142 @@
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+
+use crate::span::Span;
+use crate::zone::{DstIssue, Zone};
+
+/// Applies a chain of signed spans like `-2h` or `+1w3d -12h` to `t`.
+fn apply_offsets(t: DateTime<Utc>, s: &str) -> Result<DateTime<Utc>, String> {
+    let mut t = t;
+    let mut rest = s.trim();
+    while !rest.is_empty() {
+        let negative = match rest.chars().next() {
+            Some('+') => false,
+            Some('-') => true,
+            _ => return Err(format!("expected + or - at {:?}", rest)),
+        };
+        rest = &rest[1..];
+        let end = rest.find(['+', '-']).unwrap_or(rest.len());
+        let span = Span::parse(&rest[..end])?;
+        let span = if negative { span.negate() } else { span };
+        t = span
+            .add_to(t)
+            .ok_or_else(|| format!("{:?} is too far from now", s))?;
+        rest = rest[end..].trim_start();
+    }
+    Ok(t)
+}
+
+/// Like `str::strip_prefix`, ignoring ASCII case.
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = s.get(..prefix.len())?;
+    if head.eq_ignore_ascii_case(prefix) {
+        Some(&s[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+/// Like `str::strip_suffix`, ignoring ASCII case.
+fn strip_suffix_ignore_case<'a>(s: &'a str, suffix: &str) -> Option<&'a str> {
+    let at = s.len().checked_sub(suffix.len())?;
+    let tail = s.get(at..)?;
+    if tail.eq_ignore_ascii_case(suffix) {
+        Some(&s[..at])
+    } else {
+        None
+    }
+}
+
+fn add_span(t: DateTime<Utc>, span: Span) -> Result<DateTime<Utc>, String> {
+    span.add_to(t)
+        .ok_or_else(|| "the result is too far from now".to_string())
+}
+
+/// Parses an optional time of day, e.g. the `09:00` in `tomorrow 09:00`.
+pub(crate) fn parse_time_of_day(s: &str) -> Result<NaiveTime, String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Ok(NaiveTime::from_hms(0, 0, 0));
+    }
+    NaiveTime::parse_from_str(s, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+        .map_err(|e| format!("invalid time of day {:?}: {}", s, e))
+}
+
+/// Parses an expression relative to `now`, one of:
+///
+/// - `now`, optionally followed by offsets: `now-2h`, `now + 90m`
+/// - bare offsets: `+1w3d`, `-30s`
+/// - `3 days ago`, `in 45 minutes`, `2 hours from now`
+/// - `today`, `yesterday` or `tomorrow`, optionally with a time: `tomorrow 09:00`
+///
+/// Days start at midnight in `zone`, so only those forms can run into a DST
+/// change.
+pub fn parse_relative(
+    s: &str,
+    now: DateTime<Utc>,
+    zone: Zone,
+) -> Result<(DateTime<Utc>, Option<DstIssue>), String> {
+    // Only the keywords are case-insensitive: in the spans, `M` is months
+    // and `m` minutes.
+    let s = s.trim();
+
+    if let Some(rest) = strip_prefix_ignore_case(s, "now") {
+        return apply_offsets(now, rest).map(|t| (t, None));
+    }
+    if s.starts_with(['+', '-']) {
+        return apply_offsets(now, s).map(|t| (t, None));
+    }
+    let past = strip_suffix_ignore_case(s, " ago").map(|span| (span, true));
+    let future = strip_suffix_ignore_case(s, " from now")
+        .or_else(|| strip_prefix_ignore_case(s, "in "))
+        .map(|span| (span, false));
+    if let Some((span, negative)) = past.or(future) {
+        let span = Span::parse(span)?;
+        let span = if negative { span.negate() } else { span };
+        return add_span(now, span).map(|t| (t, None));
+    }
+
+    for &(word, days) in &[("today", 0), ("yesterday", -1), ("tomorrow", 1)] {
+        if let Some(rest) = strip_prefix_ignore_case(s, word) {
+            let date = zone.today(now) + Duration::days(days);
+            let time = parse_time_of_day(rest)?;
+            return Ok(zone.resolve(date.and_time(time)));
+        }
+    }
+
+    Err("not a relative time expression".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(s: &str) -> Result<String, String> {
+        let now = DateTime::parse_from_rfc3339("2024-01-31T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        parse_relative(s, now, Zone::Named(chrono_tz::UTC)).map(|(t, _)| t.to_rfc3339())
+    }
+
+    #[test]
+    fn capital_m_is_months() {
+        assert_eq!(eval("+1M"), Ok("2024-02-29T12:00:00+00:00".to_string()));
+        assert_eq!(eval("1M ago"), Ok("2023-12-31T12:00:00+00:00".to_string()));
+        assert_eq!(eval("in 1M"), Ok("2024-02-29T12:00:00+00:00".to_string()));
+        assert_eq!(eval("+1m"), Ok("2024-01-31T12:01:00+00:00".to_string()));
+    }
+
+    #[test]
+    fn keywords_ignore_case() {
+        assert_eq!(
+            eval("3 Days AGO"),
+            Ok("2024-01-28T12:00:00+00:00".to_string())
+        );
+        assert_eq!(eval("NOW-2h"), Ok("2024-01-31T10:00:00+00:00".to_string()));
+        assert_eq!(
+            eval("Tomorrow 09:00"),
+            Ok("2024-02-01T09:00:00+00:00".to_string())
+        );
+        assert!(eval("é ago").is_err());
+    }
+}
@@ -0,0 +1,362 @@
+use std::fmt;
+
+use chrono::{DateTime, Duration, Local, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::diff::Breakdown;
+use crate::epoch::Epoch;
+use crate::parse::ParsedTime;
+use crate::unit::Unit;
+use crate::zone::Zone;
+
+/// Coarse counts of the distance between the reported time and now.
+#[derive(Debug, Clone, Serialize)]
+pub struct Relative {
+    pub seconds: i64,
+    pub hours: i64,
+    pub days: i64,
+}
+
+impl Relative {
+    pub fn new(d: Duration) -> Relative {
+        Relative {
+            seconds: d.num_seconds(),
+            hours: d.num_hours(),
+            days: d.num_days(),
+        }
+    }
+
+    fn fmt_direction(&self, f: &mut fmt::Formatter, direction: &str) -> fmt::Result {
+        writeln!(f, "{:20}{}", format!("Seconds {}", direction), self.seconds)?;
+        if self.hours > 0 {
+            writeln!(f, "{:20}{}", format!("Hours {}", direction), self.hours)?;
+        }
+        if self.days > 0 {
+            writeln!(f, "{:20}{}", format!("Days {}", direction), self.days)?;
+        }
+        writeln!(f)
+    }
+}
+
+/// The reported time, as seen in one IANA time zone.
+#[derive(Debug, Clone, Serialize)]
+pub struct ZoneTime {
+    pub name: String,
+    pub abbreviation: String,
+    pub utc_offset: String,
+    pub rfc2822: String,
+    pub rfc3339: String,
+}
+
+impl ZoneTime {
+    pub fn new(utc_ts: DateTime<Utc>, tz: Tz) -> ZoneTime {
+        let zoned = utc_ts.with_timezone(&tz);
+        ZoneTime {
+            name: tz.name().to_string(),
+            abbreviation: zoned.format("%Z").to_string(),
+            utc_offset: zoned.format("%:z").to_string(),
+            rfc2822: zoned.to_rfc2822(),
+            rfc3339: zoned.to_rfc3339(),
+        }
+    }
+}
+
+/// The reported time rendered with a custom strftime-style format.
+#[derive(Debug, Clone, Serialize)]
+pub struct Formatted {
+    pub format: String,
+    pub zone: String,
+    pub value: String,
+}
+
+/// Optional extras to include in a `Report`.
+#[derive(Debug, Clone, Default)]
+pub struct ReportOptions {
+    /// Zones to show the time in, besides UTC and the local zone.
+    pub zones: Vec<Tz>,
+    /// Custom strftime-style formats to render the time with, and the zone
+    /// to render each one in.
+    pub formats: Vec<(String, Zone)>,
+    /// How many units to use when describing how long ago the time was, e.g.
+    /// 2 for `3 years, 2 months ago`. 0 uses all of them.
+    pub granularity: usize,
+}
+
+/// Everything `time-cli` knows about a single instant.
+///
+/// The `Display` impl renders the human-readable report; the `Serialize` impl
+/// is used for machine-readable output, and its keys are considered stable.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    /// The candidate format the input matched, if there was an input.
+    pub format: Option<String>,
+    /// The inferred resolution, if the input was a numeric epoch.
+    pub unit: Option<Unit>,
+    /// Set when the input was a wall-clock time that a DST change made
+    /// ambiguous, explaining how it was resolved.
+    pub warning: Option<String>,
+    pub unix: i64,
+    pub unix_float: f64,
+    pub unix_ms: i64,
+    pub unix_us: i64,
+    pub unix_ns: i128,
+    /// 100ns ticks since 1601, as used by Windows and Active Directory.
+    pub filetime: i64,
+    pub filetime_hex: String,
+    /// 100ns ticks since 0001-01-01, as in .NET's `DateTime.Ticks`.
+    pub dotnet_ticks: i64,
+    /// Seconds since 2001-01-01, as used by Cocoa and Core Data.
+    pub cocoa: f64,
+    /// Seconds since 1980-01-06 in GPS time, which is ahead of UTC by the
+    /// leap seconds since.
+    pub gps: i64,
+    /// Seconds since 1904-01-01, as used by HFS+.
+    pub hfs_plus: i64,
+    /// How long ago or from now the time is in calendar units, e.g.
+    /// `3 years, 2 months, 5 days ago`, or `now`.
+    pub humanized: String,
+    pub since: Option<Relative>,
+    pub until: Option<Relative>,
+    pub rfc2822_utc: String,
+    pub rfc3339_utc: String,
+    pub ymd_utc: String,
+    pub ymdh_utc: String,
+    pub rfc2822_local: String,
+    pub rfc3339_local: String,
+    pub zones: Vec<ZoneTime>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub formatted: Vec<Formatted>,
+}
+
+impl Report {
+    pub fn new(utc_ts: DateTime<Utc>, now: DateTime<Utc>, options: &ReportOptions) -> Report {
+        let local_ts = utc_ts.with_timezone(&Local);
+        let unix_ns =
+            utc_ts.timestamp() as i128 * 1_000_000_000 + utc_ts.timestamp_subsec_nanos() as i128;
+        // Any `DateTime` is far from overflowing an `i128` of nanoseconds.
+        let since = |epoch: Epoch| {
+            epoch
+                .from_unix_nanos(unix_ns)
+                .expect("nanoseconds fit in an i128")
+        };
+        let ticks = |epoch: Epoch| since(epoch).div_euclid(epoch.tick_nanos());
+        Report {
+            format: None,
+            unit: None,
+            warning: None,
+            unix: utc_ts.timestamp(),
+            // Dividing the nanoseconds would round twice and can land one ulp
+            // off, e.g. 1700000000.1230001.
+            unix_float: utc_ts.timestamp() as f64 + utc_ts.timestamp_subsec_nanos() as f64 / 1e9,
+            unix_ms: unix_ns.div_euclid(1_000_000) as i64,
+            unix_us: unix_ns.div_euclid(1_000) as i64,
+            unix_ns,
+            filetime: ticks(Epoch::FileTime) as i64,
+            filetime_hex: format!("0x{:016X}", ticks(Epoch::FileTime)),
+            dotnet_ticks: ticks(Epoch::DotNet) as i64,
+            cocoa: since(Epoch::Cocoa).div_euclid(1_000_000_000) as f64
+                + since(Epoch::Cocoa).rem_euclid(1_000_000_000) as f64 / 1e9,
+            gps: ticks(Epoch::Gps) as i64,
+            hfs_plus: ticks(Epoch::HfsPlus) as i64,
+            humanized: Breakdown::between(now.naive_utc(), utc_ts.naive_utc())
+                .humanize(options.granularity),
+            since: if utc_ts < now {
+                Some(Relative::new(now - utc_ts))
+            } else {
+                None
+            },
+            until: if utc_ts > now {
+                Some(Relative::new(utc_ts - now))
+            } else {
+                None
+            },
+            rfc2822_utc: utc_ts.to_rfc2822(),
+            rfc3339_utc: utc_ts.to_rfc3339(),
+            ymd_utc: utc_ts.format("%Y%m%d").to_string(),
+            ymdh_utc: utc_ts.format("%Y%m%d%H").to_string(),
+            rfc2822_local: local_ts.to_rfc2822(),
+            rfc3339_local: local_ts.to_rfc3339(),
+            zones: options
+                .zones
+                .iter()
+                .map(|&tz| ZoneTime::new(utc_ts, tz))
+                .collect(),
+            formatted: options
+                .formats
+                .iter()
+                .map(|&(ref format, zone)| Formatted {
+                    format: format.clone(),
+                    zone: zone.name().to_string(),
+                    value: zone.format(utc_ts, format),
+                })
+                .collect(),
+        }
+    }
+
+    /// Builds a report for a parsed input, recording how it was interpreted.
+    pub fn from_parsed(parsed: &ParsedTime, now: DateTime<Utc>, options: &ReportOptions) -> Report {
+        Report {
+            format: Some(parsed.format.clone()),
+            unit: parsed.unit,
+            warning: parsed.dst.as_ref().map(|dst| dst.to_string()),
+            ..Report::new(parsed.time, now, options)
+        }
+    }
+
+    /// The names accepted by `field`, which match the keys of the JSON output.
+    pub const FIELDS: &'static [&'static str] = &[
+        "unix",
+        "unix_float",
+        "unix_ms",
+        "unix_us",
+        "unix_ns",
+        "filetime",
+        "filetime_hex",
+        "dotnet_ticks",
+        "cocoa",
+        "gps",
+        "hfs_plus",
+        "rfc2822_utc",
+        "rfc3339_utc",
+        "ymd_utc",
+        "ymdh_utc",
+        "rfc2822_local",
+        "rfc3339_local",
+    ];
+
+    /// A single value from the report as text, or `None` if `name` isn't one
+    /// of `FIELDS`.
+    pub fn field(&self, name: &str) -> Option<String> {
+        Some(match name {
+            "unix" => self.unix.to_string(),
+            "unix_float" => decimal_seconds(self.unix_ns),
+            "unix_ms" => self.unix_ms.to_string(),
+            "unix_us" => self.unix_us.to_string(),
+            "unix_ns" => self.unix_ns.to_string(),
+            "filetime" => self.filetime.to_string(),
+            "filetime_hex" => self.filetime_hex.clone(),
+            "dotnet_ticks" => self.dotnet_ticks.to_string(),
+            "cocoa" => decimal_seconds(self.cocoa_ns()),
+            "gps" => self.gps.to_string(),
+            "hfs_plus" => self.hfs_plus.to_string(),
+            "rfc2822_utc" => self.rfc2822_utc.clone(),
+            "rfc3339_utc" => self.rfc3339_utc.clone(),
+            "ymd_utc" => self.ymd_utc.clone(),
+            "ymdh_utc" => self.ymdh_utc.clone(),
+            "rfc2822_local" => self.rfc2822_local.clone(),
+            "rfc3339_local" => self.rfc3339_local.clone(),
+            _ => return None,
+        })
+    }
+
+    fn cocoa_ns(&self) -> i128 {
+        Epoch::Cocoa
+            .from_unix_nanos(self.unix_ns)
+            .expect("nanoseconds fit in an i128")
+    }
+
+    fn precision(&self) -> usize {
+        precision(self.unix_ns)
+    }
+}
+
+/// The number of fractional digits needed to show the sub-second part of
+/// `nanos` without loss, in steps of milli, micro and nanoseconds.
+fn precision(nanos: i128) -> usize {
+    let subsec_nanos = nanos.rem_euclid(1_000_000_000);
+    if subsec_nanos % 1_000_000 == 0 {
+        3
+    } else if subsec_nanos % 1_000 == 0 {
+        6
+    } else {
+        9
+    }
+}
+
+/// `nanos` in seconds as an exact decimal string, since an `f64` can't
+/// represent nanoseconds this far from zero.
+pub(crate) fn decimal_seconds(nanos: i128) -> String {
+    let sign = if nanos < 0 { "-" } else { "" };
+    let abs = nanos.abs();
+    let frac = format!("{:09}", abs % 1_000_000_000);
+    format!(
+        "{}{}.{}",
+        sign,
+        abs / 1_000_000_000,
+        &frac[..precision(nanos)]
+    )
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref format) = self.format {
+            writeln!(f, "{:20}{}", "Parsed as:", format)?;
+            if let Some(ref warning) = self.warning {
+                writeln!(f, "{:20}{}", "Warning:", warning)?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "{:20}{:.03}", "Unix time:", self.unix)?;
+        writeln!(
+            f,
+            "{:20}{}",
+            "Unix time (float):",
+            decimal_seconds(self.unix_ns)
+        )?;
+        writeln!(f, "{:20}{}", "Unix time (ms):", self.unix_ms)?;
+        if self.precision() > 3 {
+            writeln!(f, "{:20}{}", "Unix time (us):", self.unix_us)?;
+        }
+        if self.precision() > 6 {
+            writeln!(f, "{:20}{}", "Unix time (ns):", self.unix_ns)?;
+        }
+        writeln!(f)?;
+
+        writeln!(
+            f,
+            "{:20}{} ({})",
+            "FILETIME:", self.filetime, self.filetime_hex
+        )?;
+        writeln!(f, "{:20}{}", ".NET ticks:", self.dotnet_ticks)?;
+        writeln!(
+            f,
+            "{:20}{}",
+            "Cocoa time:",
+            decimal_seconds(self.cocoa_ns())
+        )?;
+        writeln!(f, "{:20}{}", "GPS time:", self.gps)?;
+        writeln!(f, "{:20}{}", "HFS+ time:", self.hfs_plus)?;
+        writeln!(f)?;
+
+        if let Some(ref since) = self.since {
+            writeln!(f, "{:20}{}", "Relative:", self.humanized)?;
+            since.fmt_direction(f, "since")?;
+        } else if let Some(ref until) = self.until {
+            writeln!(f, "{:20}{}", "Relative:", self.humanized)?;
+            until.fmt_direction(f, "until")?;
+        }
+
+        writeln!(f, "{:20}{}", "RFC2822 UTC:", self.rfc2822_utc)?;
+        writeln!(f, "{:20}{}", "RFC3339 UTC:", self.rfc3339_utc)?;
+        writeln!(f, "{:20}{}", "YMD UTC:", self.ymd_utc)?;
+        writeln!(f, "{:20}{}", "YMDH UTC:", self.ymdh_utc)?;
+        writeln!(f)?;
+
+        writeln!(f, "{:20}{}", "RFC2822 Local:", self.rfc2822_local)?;
+        writeln!(f, "{:20}{}", "RFC3339 Local:", self.rfc3339_local)?;
+
+        for zone in &self.zones {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "{:20}{} ({}, UTC{})",
+                "Zone:", zone.name, zone.abbreviation, zone.utc_offset
+            )?;
+            writeln!(f, "{:20}{}", "RFC2822:", zone.rfc2822)?;
+            writeln!(f, "{:20}{}", "RFC3339:", zone.rfc3339)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,441 @@
+use std::convert::TryFrom;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+
+use crate::zone::{DstIssue, Zone};
+
+/// A length of time as people write it, e.g. `1w3d` or `2 months 5 hours`.
+///
+/// Months and years don't have a fixed length, so they are kept apart from
+/// the exact part and applied on the calendar: adding a month to January 31st
+/// lands on the last day of February. Days and weeks are kept apart too, so
+/// they can either be read as 24 hours or as calendar days, which differ when
+/// a DST change happens in between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub months: i64,
+    pub days: i64,
+    pub exact: Duration,
+}
+
+/// The units a span can be written in, and how to spell them. Case doesn't
+/// matter, except that `M` is months and `m` minutes, as in date math.
+const UNITS: &[(&[&str], SpanUnit)] = &[
+    (
+        &["ns", "nsec", "nanosecond", "nanoseconds"],
+        SpanUnit::Exact(1),
+    ),
+    (
+        &["us", "\u{b5}s", "usec", "microsecond", "microseconds"],
+        SpanUnit::Exact(1_000),
+    ),
+    (
+        &["ms", "msec", "millisecond", "milliseconds"],
+        SpanUnit::Exact(1_000_000),
+    ),
+    (
+        &["s", "sec", "secs", "second", "seconds"],
+        SpanUnit::Exact(1_000_000_000),
+    ),
+    (
+        &["m", "min", "mins", "minute", "minutes"],
+        SpanUnit::Exact(60 * 1_000_000_000),
+    ),
+    (
+        &["h", "hr", "hrs", "hour", "hours"],
+        SpanUnit::Exact(3600 * 1_000_000_000),
+    ),
+    (&["d", "day", "days"], SpanUnit::Days(1)),
+    (&["w", "wk", "wks", "week", "weeks"], SpanUnit::Days(7)),
+    (&["M", "mo", "mon", "month", "months"], SpanUnit::Months(1)),
+    (&["y", "yr", "yrs", "year", "years"], SpanUnit::Months(12)),
+];
+
+fn find_unit<F: Fn(&str) -> bool>(matches: F) -> Option<SpanUnit> {
+    UNITS
+        .iter()
+        .find(|(names, _)| names.iter().any(|name| matches(name)))
+        .map(|&(_, unit)| unit)
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SpanUnit {
+    /// A fixed number of nanoseconds.
+    Exact(i64),
+    /// A number of days.
+    Days(i64),
+    /// A number of calendar months.
+    Months(i64),
+}
+
+impl Span {
+    pub fn zero() -> Span {
+        Span {
+            months: 0,
+            days: 0,
+            exact: Duration::zero(),
+        }
+    }
+
+    /// Parses a sequence of `<number><unit>` terms, optionally separated by
+    /// whitespace, commas or `and`, e.g. `1w3d`, `90m`, `1.5h` or
+    /// `3 days, 4 hours`.
+    pub fn parse(s: &str) -> Result<Span, String> {
+        let mut span = Span::zero();
+        let mut rest = s.trim();
+        if rest.is_empty() {
+            return Err("empty duration".to_string());
+        }
+        while !rest.is_empty() {
+            let (whole, frac, r) = split_number(rest)?;
+            rest = r.trim_start();
+
+            let len = rest
+                .find(|c: char| !c.is_alphabetic())
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            if word.is_empty() {
+                return Err(format!("expected a unit after {:?}", whole));
+            }
+            let unit = find_unit(|name| name == word)
+                .or_else(|| find_unit(|name| name.eq_ignore_ascii_case(word)))
+                .ok_or_else(|| format!("unknown unit {:?}", word))?;
+            span = span.add_term(unit, whole, frac, s)?;
+
+            rest = rest[len..].trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+            if let Some(r) = rest.strip_prefix("and ") {
+                rest = r.trim_start();
+            }
+        }
+        Ok(span)
+    }
+
+    /// Parses an ISO 8601 duration, e.g. `PT36H`, `P1DT2H30M` or `P1Y2M`.
+    pub fn parse_iso8601(s: &str) -> Result<Span, String> {
+        let s = s.trim();
+        let body = s
+            .strip_prefix('P')
+            .or_else(|| s.strip_prefix('p'))
+            .ok_or("ISO 8601 durations start with P")?;
+        let (date, time) = match body.find(['T', 't']) {
+            Some(t) => (&body[..t], Some(&body[t + 1..])),
+            None => (body, None),
+        };
+        if date.is_empty() && time.unwrap_or("").is_empty() {
+            return Err("no fields after P".to_string());
+        }
+
+        let mut span = Span::zero();
+        for (part, designators) in [
+            (
+                date,
+                &[
+                    ('Y', SpanUnit::Months(12)),
+                    ('M', SpanUnit::Months(1)),
+                    ('W', SpanUnit::Days(7)),
+                    ('D', SpanUnit::Days(1)),
+                ][..],
+            ),
+            (
+                time.unwrap_or(""),
+                &[
+                    ('H', SpanUnit::Exact(3600 * 1_000_000_000)),
+                    ('M', SpanUnit::Exact(60 * 1_000_000_000)),
+                    ('S', SpanUnit::Exact(1_000_000_000)),
+                ][..],
+            ),
+        ] {
+            // Fields must come in order, so each designator is looked for
+            // after the previous one.
+            let mut designators = designators.iter();
+            let mut rest = part;
+            while !rest.is_empty() {
+                let (whole, frac, r) = split_number(rest)?;
+                let c = r
+                    .chars()
+                    .next()
+                    .ok_or_else(|| format!("missing designator after {:?}", whole))?
+                    .to_ascii_uppercase();
+                let unit = designators
+                    .find(|&&(d, _)| d == c)
+                    .map(|&(_, unit)| unit)
+                    .ok_or_else(|| format!("unexpected designator {:?}", c))?;
+                span = span.add_term(unit, whole, frac, s)?;
+                rest = &r[1..];
+            }
+        }
+        Ok(span)
+    }
+
+    /// Parses a clock-style duration, `H:MM:SS` or `H:MM`, where the seconds
+    /// may have a fraction, e.g. `01:30:00` or `36:00:00.5`.
+    pub fn parse_clock(s: &str) -> Result<Span, String> {
+        let s = s.trim();
+        let parts: Vec<&str> = s.split(':').collect();
+        if parts.len() < 2 || parts.len() > 3 {
+            return Err("expected H:MM:SS or H:MM".to_string());
+        }
+        let mut span = Span::zero();
+        for (i, (part, unit)) in parts
+            .iter()
+            .zip(&[
+                SpanUnit::Exact(3600 * 1_000_000_000),
+                SpanUnit::Exact(60 * 1_000_000_000),
+                SpanUnit::Exact(1_000_000_000),
+            ])
+            .enumerate()
+        {
+            let (whole, frac, rest) = split_number(part)?;
+            if !rest.is_empty() || (!frac.is_empty() && i < parts.len() - 1) {
+                return Err(format!("unexpected {:?}", part));
+            }
+            if i > 0 && (whole.len() != 2 || whole >= "60") {
+                return Err(format!(
+                    "expected two digits from 00 to 59, not {:?}",
+                    whole
+                ));
+            }
+            span = span.add_term(*unit, whole, frac, s)?;
+        }
+        Ok(span)
+    }
+
+    /// Adds `<whole>.<frac>` of `unit`, where the fraction may be empty.
+    /// Fractions of days are added as exact time, and fractions of months
+    /// are rejected, since months have no fixed length.
+    fn add_term(self, unit: SpanUnit, whole: &str, frac: &str, s: &str) -> Result<Span, String> {
+        let too_large = || format!("duration {:?} is too large", s);
+        let n = whole
+            .parse::<i64>()
+            .map_err(|e| format!("{} in {:?}", e, whole))?;
+        let span = self.checked_add(unit, n).ok_or_else(too_large)?;
+        if frac.is_empty() {
+            return Ok(span);
+        }
+        let per = match unit {
+            SpanUnit::Exact(nanos) => nanos as i128,
+            SpanUnit::Days(days) => days as i128 * 86400 * 1_000_000_000,
+            SpanUnit::Months(_) => {
+                return Err("months and years can't be fractional".to_string());
+            }
+        };
+        // Digits past the 18th are below a nanosecond of even a week.
+        let frac = &frac[..frac.len().min(18)];
+        let nanos = frac.parse::<i128>().unwrap_or(0) * per / 10i128.pow(frac.len() as u32);
+        span.checked_add(SpanUnit::Exact(1), nanos as i64)
+            .ok_or_else(too_large)
+    }
+
+    fn checked_add(self, unit: SpanUnit, n: i64) -> Option<Span> {
+        match unit {
+            SpanUnit::Exact(nanos) => Some(Span {
+                exact: self
+                    .exact
+                    .checked_add(&Duration::nanoseconds(n.checked_mul(nanos)?))?,
+                ..self
+            }),
+            SpanUnit::Days(days) => {
+                let days = self.days.checked_add(n.checked_mul(days)?)?;
+                // Keep days within what a `Duration` can hold in nanoseconds,
+                // so they can always be read as 24 hours.
+                days.checked_mul(86400 * 1_000_000_000)?;
+                Some(Span { days, ..self })
+            }
+            SpanUnit::Months(months) => Some(Span {
+                months: self.months.checked_add(n.checked_mul(months)?)?,
+                ..self
+            }),
+        }
+    }
+
+    /// The span pointing the other way in time.
+    pub fn negate(self) -> Span {
+        Span {
+            months: -self.months,
+            days: -self.days,
+            exact: -self.exact,
+        }
+    }
+
+    /// The span as a fixed length of time, reading days as 24 hours, or
+    /// `None` if it has months or years, which have no fixed length.
+    pub fn to_exact(self) -> Option<Duration> {
+        if self.months != 0 {
+            return None;
+        }
+        Duration::seconds(self.days.checked_mul(86400)?).checked_add(&self.exact)
+    }
+
+    /// Applies the span to `t`: calendar months first, then days as 24 hours
+    /// each, then the exact part. Returns `None` if the result can't be
+    /// represented.
+    pub fn add_to(self, t: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        add_months(t, self.months)?
+            .checked_add_signed(Duration::seconds(self.days.checked_mul(86400)?))?
+            .checked_add_signed(self.exact)
+    }
+
+    /// Applies the span to `t` on the calendar in `zone`: months and days move
+    /// the date while keeping the wall-clock time, then the exact part is
+    /// added. Returns `None` if the result can't be represented.
+    pub fn add_in(self, t: DateTime<Utc>, zone: Zone) -> Option<(DateTime<Utc>, Option<DstIssue>)> {
+        let wall = zone.wall_clock(t);
+        let date = shift_months(wall.date(), self.months)?
+            .checked_add_signed(Duration::days(self.days))?;
+        let (t, dst) = zone.resolve(date.and_time(wall.time()));
+        Some((t.checked_add_signed(self.exact)?, dst))
+    }
+}
+
+/// Splits a leading `<digits>` or `<digits>.<digits>` off `s`, returning the
+/// whole part, the fraction and the rest.
+fn split_number(s: &str) -> Result<(&str, &str, &str), String> {
+    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    if digits == 0 {
+        return Err(format!("expected a number at {:?}", s));
+    }
+    let (whole, rest) = s.split_at(digits);
+    match rest.strip_prefix('.') {
+        Some(rest) => {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            Ok((whole, &rest[..digits], &rest[digits..]))
+        }
+        None => Ok((whole, "", rest)),
+    }
+}
+
+/// The number of days in a month, or `None` if the year is out of range.
+pub(crate) fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    (28..=31)
+        .rev()
+        .find(|&day| NaiveDate::from_ymd_opt(year, month, day).is_some())
+}
+
+/// Moves `t` by a number of calendar months, keeping the time of day and
+/// clamping the day to the end of a shorter month.
+pub fn add_months(t: DateTime<Utc>, months: i64) -> Option<DateTime<Utc>> {
+    let date = shift_months(t.date().naive_utc(), months)?;
+    Some(DateTime::<Utc>::from_utc(date.and_time(t.time()), Utc))
+}
+
+/// Moves a calendar date by a number of months, clamping the day to the end
+/// of a shorter month.
+pub(crate) fn shift_months(date: NaiveDate, months: i64) -> Option<NaiveDate> {
+    if months == 0 {
+        return Some(date);
+    }
+    let total = (date.year() as i64 * 12 + date.month0() as i64).checked_add(months)?;
+    let year = i32::try_from(total.div_euclid(12)).ok()?;
+    let month = total.rem_euclid(12) as u32 + 1;
+    let day = date.day().min(days_in_month(year, month)?);
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capital_m_is_months() {
+        assert_eq!(Span::parse("1M").map(|s| s.months), Ok(1));
+        assert_eq!(Span::parse("1m").map(|s| s.exact), Ok(Duration::minutes(1)));
+        assert_eq!(
+            Span::parse("1MIN").map(|s| s.exact),
+            Ok(Duration::minutes(1))
+        );
+        assert_eq!(
+            Span::parse("1MS").map(|s| s.exact),
+            Ok(Duration::milliseconds(1))
+        );
+    }
+
+    fn span(months: i64, days: i64, exact: Duration) -> Span {
+        Span {
+            months,
+            days,
+            exact,
+        }
+    }
+
+    #[test]
+    fn parses_units() {
+        assert_eq!(Span::parse("1w3d"), Ok(span(0, 10, Duration::zero())));
+        assert_eq!(
+            Span::parse("2 months, 5 hours and 1.5s"),
+            Ok(span(2, 0, Duration::milliseconds(5 * 3_600_000 + 1_500)))
+        );
+        assert_eq!(Span::parse("1.5d"), Ok(span(0, 1, Duration::hours(12))));
+        assert!(Span::parse("1.5mo").is_err());
+        assert!(Span::parse("5").is_err());
+        assert!(Span::parse("5 fortnights").is_err());
+        assert!(Span::parse("9223372036854775807d").is_err());
+    }
+
+    #[test]
+    fn parses_iso8601() {
+        assert_eq!(
+            Span::parse_iso8601("P1DT2H30M"),
+            Ok(span(0, 1, Duration::minutes(150)))
+        );
+        assert_eq!(
+            Span::parse_iso8601("PT36H"),
+            Ok(span(0, 0, Duration::hours(36)))
+        );
+        assert_eq!(
+            Span::parse_iso8601("P1Y2M3W"),
+            Ok(span(14, 21, Duration::zero()))
+        );
+        assert_eq!(
+            Span::parse_iso8601("PT0.5S"),
+            Ok(span(0, 0, Duration::milliseconds(500)))
+        );
+        for s in &["P", "PT", "1D", "PT1D", "P1H", "P1D2Y", "P1"] {
+            assert!(Span::parse_iso8601(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn parses_clock() {
+        assert_eq!(
+            Span::parse_clock("01:30:00"),
+            Ok(span(0, 0, Duration::minutes(90)))
+        );
+        assert_eq!(
+            Span::parse_clock("36:00:00.5"),
+            Ok(span(0, 0, Duration::milliseconds(36 * 3_600_000 + 500)))
+        );
+        assert_eq!(
+            Span::parse_clock("1:05"),
+            Ok(span(0, 0, Duration::minutes(65)))
+        );
+        for s in &["1", "1:60", "1:5", "1.5:00", "1:00:00:00", "1:00é"] {
+            assert!(Span::parse_clock(s).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn adds_on_the_calendar() {
+        let t = DateTime::parse_from_rfc3339("2024-01-31T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let (moved, _) = span(1, 1, Duration::hours(1))
+            .add_in(t, Zone::Named(chrono_tz::UTC))
+            .unwrap();
+        assert_eq!(moved.to_rfc3339(), "2024-03-01T13:00:00+00:00");
+        assert_eq!(
+            span(0, 1, Duration::zero()).to_exact(),
+            Some(Duration::hours(24))
+        );
+        assert_eq!(span(1, 0, Duration::zero()).to_exact(), None);
+    }
+
+    #[test]
+    fn shifting_months_too_far_is_none() {
+        let date = NaiveDate::from_ymd(2024, 1, 31);
+        assert_eq!(shift_months(date, 1), NaiveDate::from_ymd_opt(2024, 2, 29));
+        assert_eq!(shift_months(date, i64::MAX), None);
+        assert_eq!(shift_months(date, i64::MIN), None);
+    }
+}
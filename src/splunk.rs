@@ -0,0 +1,189 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, Utc, Weekday};
+
+use crate::calendar::Period;
+use crate::zone::{DstIssue, Zone};
+
+const UNITS: &[(&str, Period)] = &[
+    ("s", Period::Second),
+    ("sec", Period::Second),
+    ("secs", Period::Second),
+    ("second", Period::Second),
+    ("seconds", Period::Second),
+    ("m", Period::Minute),
+    ("min", Period::Minute),
+    ("mins", Period::Minute),
+    ("minute", Period::Minute),
+    ("minutes", Period::Minute),
+    ("h", Period::Hour),
+    ("hr", Period::Hour),
+    ("hrs", Period::Hour),
+    ("hour", Period::Hour),
+    ("hours", Period::Hour),
+    ("d", Period::Day),
+    ("day", Period::Day),
+    ("days", Period::Day),
+    ("w", Period::Week),
+    ("week", Period::Week),
+    ("weeks", Period::Week),
+    ("mon", Period::Month),
+    ("month", Period::Month),
+    ("months", Period::Month),
+    ("q", Period::Quarter),
+    ("qtr", Period::Quarter),
+    ("qtrs", Period::Quarter),
+    ("quarter", Period::Quarter),
+    ("quarters", Period::Quarter),
+    ("y", Period::Year),
+    ("yr", Period::Year),
+    ("yrs", Period::Year),
+    ("year", Period::Year),
+    ("years", Period::Year),
+];
+
+fn parse_unit(s: &str) -> Result<Period, String> {
+    UNITS
+        .iter()
+        .find(|&&(name, _)| name == s)
+        .map(|&(_, period)| period)
+        .ok_or_else(|| format!("unknown unit {:?}", s))
+}
+
+/// Snaps `wall` back to the start of the most recent `weekday`, which is
+/// today's midnight if today is that day.
+fn snap_to_weekday(wall: NaiveDateTime, weekday: Weekday) -> NaiveDateTime {
+    let today = Period::Day.floor(wall);
+    let back = (7 + today.weekday().num_days_from_sunday() - weekday.num_days_from_sunday()) % 7;
+    today - Duration::days(back as i64)
+}
+
+/// Parses Splunk-style relative time modifiers, e.g. `-24h@h`, `@w1` or
+/// `-1mon@mon+7d`.
+///
+/// The modifier is a chain of offsets like `-2d` or `+30m`, where the count
+/// defaults to 1, and snaps like `@d` that round down to the start of a unit.
+/// `@w0` through `@w6` snap to the most recent Sunday through Saturday, and
+/// `@w7` is Sunday again; a plain `@w` is the same as `@w0`. Hours and smaller
+/// offsets are exact, while days and larger, as well as all snaps, follow the
+/// calendar in `zone`.
+pub fn parse_splunk(
+    s: &str,
+    now: DateTime<Utc>,
+    zone: Zone,
+) -> Result<(DateTime<Utc>, Option<DstIssue>), String> {
+    let s = s.trim();
+    if !s.starts_with(['+', '-', '@']) {
+        return Err("expected +, - or @ at the start of a time modifier".to_string());
+    }
+
+    let mut t = now;
+    let mut dst = None;
+    let mut rest = s;
+    while let Some(op) = rest.chars().next() {
+        rest = &rest[1..];
+        let end = rest.find(['+', '-', '@']).unwrap_or(rest.len());
+        let (term, next) = rest.split_at(end);
+        rest = next;
+        let wall = zone.wall_clock(t);
+        let moved = match op {
+            '@' => {
+                // Snaps put the count after the unit, e.g. `@w1`.
+                let digits = term
+                    .find(|c: char| c.is_ascii_digit())
+                    .unwrap_or(term.len());
+                let (unit, day) = term.split_at(digits);
+                match (parse_unit(unit)?, day) {
+                    (Period::Week, day) => {
+                        let weekday = match day {
+                            "" | "0" | "7" => Weekday::Sun,
+                            "1" => Weekday::Mon,
+                            "2" => Weekday::Tue,
+                            "3" => Weekday::Wed,
+                            "4" => Weekday::Thu,
+                            "5" => Weekday::Fri,
+                            "6" => Weekday::Sat,
+                            _ => return Err(format!("unknown weekday in snap {:?}", term)),
+                        };
+                        snap_to_weekday(wall, weekday)
+                    }
+                    (period, "") => period.floor(wall),
+                    _ => return Err(format!("unexpected count in snap {:?}", term)),
+                }
+            }
+            _ => {
+                let digits = term
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(term.len());
+                let (count, unit) = term.split_at(digits);
+                let n = if count.is_empty() {
+                    1
+                } else {
+                    count
+                        .parse::<i64>()
+                        .map_err(|e| format!("{} in {:?}", e, count))?
+                };
+                let n = if op == '-' { -n } else { n };
+                let period = parse_unit(unit)?;
+                if period.is_exact() {
+                    t = period
+                        .shift_exact(t, n)
+                        .ok_or_else(|| "date is out of range".to_string())?;
+                    continue;
+                }
+                period
+                    .shift(wall, n)
+                    .ok_or_else(|| "date is out of range".to_string())?
+            }
+        };
+        let (resolved, issue) = zone.resolve(moved);
+        t = resolved;
+        dst = issue;
+    }
+    Ok((t, dst))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn eval(s: &str, now: &str, zone: &str) -> Result<DateTime<Utc>, String> {
+        parse_splunk(s, at(now), zone.parse().unwrap()).map(|(t, _)| t)
+    }
+
+    #[test]
+    fn snaps_to_weekdays() {
+        // A Friday.
+        let now = "2024-03-15T12:34:56Z";
+        assert_eq!(eval("@w1", now, "UTC"), Ok(at("2024-03-11T00:00:00Z")));
+        assert_eq!(eval("@w5", now, "UTC"), Ok(at("2024-03-15T00:00:00Z")));
+        assert_eq!(eval("@w0", now, "UTC"), Ok(at("2024-03-10T00:00:00Z")));
+        assert_eq!(eval("@w7", now, "UTC"), eval("@w", now, "UTC"));
+        assert!(eval("@w8", now, "UTC").is_err());
+    }
+
+    #[test]
+    fn chains_offsets_and_snaps() {
+        let now = "2024-03-15T12:34:56Z";
+        assert_eq!(
+            eval("-1mon@mon+7d", now, "UTC"),
+            Ok(at("2024-02-08T00:00:00Z"))
+        );
+        assert_eq!(eval("-24h@h", now, "UTC"), Ok(at("2024-03-14T12:00:00Z")));
+        assert_eq!(eval("-d", now, "UTC"), Ok(at("2024-03-14T12:34:56Z")));
+        assert_eq!(eval("@q", now, "UTC"), Ok(at("2024-01-01T00:00:00Z")));
+        assert!(eval("-1fortnight", now, "UTC").is_err());
+    }
+
+    #[test]
+    fn snaps_follow_the_zone() {
+        // 2024-03-15T01:00:00-07:00 in Los Angeles.
+        let now = "2024-03-15T08:00:00Z";
+        assert_eq!(
+            eval("@d", now, "America/Los_Angeles"),
+            Ok(at("2024-03-15T07:00:00Z"))
+        );
+    }
+}
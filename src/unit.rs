@@ -0,0 +1,71 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+/// The resolution of a numeric epoch timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Unit {
+    #[serde(rename = "s")]
+    Seconds,
+    #[serde(rename = "ms")]
+    Milliseconds,
+    #[serde(rename = "us")]
+    Microseconds,
+    #[serde(rename = "ns")]
+    Nanoseconds,
+}
+
+impl Unit {
+    /// All units, from coarsest to finest. This is also the order in which
+    /// they're guessed at, since a small value is more likely to be seconds.
+    pub const ALL: [Unit; 4] = [
+        Unit::Seconds,
+        Unit::Milliseconds,
+        Unit::Microseconds,
+        Unit::Nanoseconds,
+    ];
+
+    /// How many of this unit make up one second.
+    pub fn per_second(self) -> i64 {
+        match self {
+            Unit::Seconds => 1,
+            Unit::Milliseconds => 1_000,
+            Unit::Microseconds => 1_000_000,
+            Unit::Nanoseconds => 1_000_000_000,
+        }
+    }
+
+    /// The short name used on the command line and in machine-readable output.
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            Unit::Seconds => "s",
+            Unit::Milliseconds => "ms",
+            Unit::Microseconds => "us",
+            Unit::Nanoseconds => "ns",
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Unit::Seconds => "seconds",
+            Unit::Milliseconds => "milliseconds",
+            Unit::Microseconds => "microseconds",
+            Unit::Nanoseconds => "nanoseconds",
+        })
+    }
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Unit, String> {
+        Unit::ALL
+            .iter()
+            .cloned()
+            .find(|u| u.abbreviation() == s)
+            .ok_or_else(|| format!("unknown unit {}, expected one of s, ms, us, ns", s))
+    }
+}
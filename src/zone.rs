@@ -0,0 +1,249 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{
+    DateTime, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+
+/// Common abbreviations, and their offsets east of UTC in seconds. Ambiguous
+/// abbreviations take their most common meaning, e.g. `CST` is US Central and
+/// `IST` is India.
+const ABBREVIATIONS: &[(&str, i32)] = &[
+    ("UTC", 0),
+    ("GMT", 0),
+    ("Z", 0),
+    ("WET", 0),
+    ("WEST", 3600),
+    ("BST", 3600),
+    ("CET", 3600),
+    ("CEST", 2 * 3600),
+    ("EET", 2 * 3600),
+    ("EEST", 3 * 3600),
+    ("MSK", 3 * 3600),
+    ("IST", 5 * 3600 + 1800),
+    ("SGT", 8 * 3600),
+    ("HKT", 8 * 3600),
+    ("AWST", 8 * 3600),
+    ("JST", 9 * 3600),
+    ("KST", 9 * 3600),
+    ("ACST", 9 * 3600 + 1800),
+    ("ACDT", 10 * 3600 + 1800),
+    ("AEST", 10 * 3600),
+    ("AEDT", 11 * 3600),
+    ("NZST", 12 * 3600),
+    ("NZDT", 13 * 3600),
+    ("HST", -10 * 3600),
+    ("AKST", -9 * 3600),
+    ("AKDT", -8 * 3600),
+    ("PST", -8 * 3600),
+    ("PDT", -7 * 3600),
+    ("MST", -7 * 3600),
+    ("MDT", -6 * 3600),
+    ("CST", -6 * 3600),
+    ("CDT", -5 * 3600),
+    ("EST", -5 * 3600),
+    ("EDT", -4 * 3600),
+];
+
+/// A time zone named at the end of an input, e.g. `America/New_York` or `PST`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zone {
+    /// An IANA zone, whose offset depends on the date.
+    Named(Tz),
+    /// An abbreviation, which always means the same fixed offset.
+    Abbreviation(&'static str, FixedOffset),
+}
+
+impl Zone {
+    /// What clocks in this zone showed at `t`.
+    pub fn wall_clock(self, t: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Zone::Named(tz) => t.with_timezone(&tz).naive_local(),
+            Zone::Abbreviation(_, offset) => t.with_timezone(&offset).naive_local(),
+        }
+    }
+
+    /// The IANA name or abbreviation this zone was given as.
+    pub fn name(self) -> &'static str {
+        match self {
+            Zone::Named(tz) => tz.name(),
+            Zone::Abbreviation(name, _) => name,
+        }
+    }
+
+    /// Renders `t` as seen in this zone with a strftime-style format. For
+    /// abbreviations, `%Z` is the abbreviation rather than the offset.
+    pub fn format(self, t: DateTime<Utc>, fmt: &str) -> String {
+        match self {
+            Zone::Named(tz) => t.with_timezone(&tz).format(fmt).to_string(),
+            Zone::Abbreviation(name, offset) => t
+                .with_timezone(&offset)
+                .format(&fmt.replace("%Z", name))
+                .to_string(),
+        }
+    }
+
+    /// The offset from UTC in this zone at `t`.
+    pub fn offset_at(self, t: DateTime<Utc>) -> FixedOffset {
+        match self {
+            Zone::Named(tz) => tz.offset_from_utc_datetime(&t.naive_utc()).fix(),
+            Zone::Abbreviation(_, offset) => offset,
+        }
+    }
+
+    /// The calendar date in this zone at `now`.
+    pub fn today(self, now: DateTime<Utc>) -> NaiveDate {
+        self.wall_clock(now).date()
+    }
+
+    /// Finds the instant at which clocks in this zone showed `wall`.
+    ///
+    /// Wall times skipped by a DST change are read with the offset from before
+    /// the change, which lands them after it, e.g. 02:30 on the morning clocks
+    /// jump from 02:00 to 03:00 becomes 03:30. Wall times that happen twice
+    /// resolve to the earlier instant.
+    pub fn resolve(self, wall: NaiveDateTime) -> (DateTime<Utc>, Option<DstIssue>) {
+        let tz = match self {
+            Zone::Named(tz) => tz,
+            Zone::Abbreviation(_, offset) => {
+                return (
+                    DateTime::<Utc>::from_utc(wall - offset_duration(offset), Utc),
+                    None,
+                )
+            }
+        };
+        match tz.from_local_datetime(&wall) {
+            LocalResult::Single(t) => (t.with_timezone(&Utc), None),
+            LocalResult::Ambiguous(earlier, later) => (
+                earlier.with_timezone(&Utc),
+                Some(DstIssue::Repeated {
+                    zone: tz.name(),
+                    wall,
+                    later: later.with_timezone(&Utc),
+                }),
+            ),
+            LocalResult::None => {
+                // DST changes are months apart, so a day earlier is safely
+                // before this one.
+                let before = tz
+                    .offset_from_utc_datetime(&(wall - Duration::days(1)))
+                    .fix();
+                (
+                    DateTime::<Utc>::from_utc(wall - offset_duration(before), Utc),
+                    Some(DstIssue::Skipped {
+                        zone: tz.name(),
+                        wall,
+                    }),
+                )
+            }
+        }
+    }
+}
+
+fn offset_duration(offset: FixedOffset) -> Duration {
+    Duration::seconds(offset.local_minus_utc() as i64)
+}
+
+impl FromStr for Zone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Zone, String> {
+        if let Some(&(name, secs)) = ABBREVIATIONS
+            .iter()
+            .find(|&&(name, _)| name.eq_ignore_ascii_case(s))
+        {
+            return Ok(Zone::Abbreviation(name, FixedOffset::east(secs)));
+        }
+        s.parse::<Tz>().map(Zone::Named)
+    }
+}
+
+/// A wall-clock time that doesn't map to exactly one instant in its zone.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DstIssue {
+    /// Clocks jumped forward over this time, so it never happened.
+    Skipped {
+        zone: &'static str,
+        wall: NaiveDateTime,
+    },
+    /// Clocks fell back over this time, so it happened twice. `later` is the
+    /// second occurrence.
+    Repeated {
+        zone: &'static str,
+        wall: NaiveDateTime,
+        later: DateTime<Utc>,
+    },
+}
+
+impl fmt::Display for DstIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DstIssue::Skipped { zone, wall } => write!(
+                f,
+                "{} never happened in {} because clocks moved forward; read with the offset from before the change",
+                wall, zone
+            ),
+            DstIssue::Repeated { zone, wall, later } => write!(
+                f,
+                "{} happened twice in {} because clocks moved back; using the first, the second is {}",
+                wall,
+                zone,
+                later.to_rfc3339()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wall(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn skipped_times_use_the_offset_from_before() {
+        let zone: Zone = "America/Los_Angeles".parse().unwrap();
+        let (t, issue) = zone.resolve(wall("2024-03-10 02:30"));
+        assert_eq!(t, utc("2024-03-10T10:30:00Z"));
+        assert_eq!(
+            issue,
+            Some(DstIssue::Skipped {
+                zone: "America/Los_Angeles",
+                wall: wall("2024-03-10 02:30"),
+            })
+        );
+    }
+
+    #[test]
+    fn repeated_times_use_the_first() {
+        let zone: Zone = "America/Los_Angeles".parse().unwrap();
+        let (t, issue) = zone.resolve(wall("2024-11-03 01:30"));
+        assert_eq!(t, utc("2024-11-03T08:30:00Z"));
+        assert_eq!(
+            issue,
+            Some(DstIssue::Repeated {
+                zone: "America/Los_Angeles",
+                wall: wall("2024-11-03 01:30"),
+                later: utc("2024-11-03T09:30:00Z"),
+            })
+        );
+    }
+
+    #[test]
+    fn abbreviations_are_fixed_offsets() {
+        let zone: Zone = "pst".parse().unwrap();
+        assert_eq!(zone.name(), "PST");
+        // Even in July, when Los Angeles is on PDT.
+        let (t, issue) = zone.resolve(wall("2024-07-01 12:00"));
+        assert_eq!(t, utc("2024-07-01T20:00:00Z"));
+        assert_eq!(issue, None);
+        assert!("Mars/Olympus_Mons".parse::<Zone>().is_err());
+    }
+}